@@ -0,0 +1,352 @@
+//! Expected answers recorded in a TOML file, keyed by day and part:
+//!
+//! ```toml
+//! [day1]
+//! part1 = 142
+//! part2 = 281
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::registry::Day;
+use crate::runner;
+use crate::solver::Answer;
+use crate::solver::Part;
+use crate::store::InputStore;
+
+/// The expected answers for one day.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct DayAnswers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part1: Option<Answer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part2: Option<Answer>,
+}
+
+/// The contents of an answers file.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Answers {
+    days: BTreeMap<String, DayAnswers>,
+}
+
+/// The outcome of checking one answer.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Fail { expected: Answer, actual: Answer },
+    Missing,
+    Error { message: String },
+}
+
+/// The status of a single day and part.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Verification {
+    pub day: u32,
+    pub part: Part,
+    #[serde(flatten)]
+    pub status: Status,
+}
+
+fn day_key(day: u32) -> String {
+    format!("day{}", day)
+}
+
+// The day a table like `[day4]` is for.
+fn key_day(key: &str) -> Option<u32> {
+    let day = key.strip_prefix("day")?.parse().ok()?;
+    (day_key(day) == key).then_some(day)
+}
+
+impl Answers {
+    /// Parses the TOML contents of an answers file. Every table must be
+    /// named for a day, like `[day4]`.
+    pub fn parse(input: &str) -> Result<Answers> {
+        let answers: Answers = toml::from_str(input).map_err(|err: toml::de::Error| {
+            let offset = err.span().map(|span| span.start).unwrap_or(0);
+            Error::parse_at(input, offset, err.message())
+        })?;
+
+        if let Some(key) = answers.days.keys().find(|key| key_day(key).is_none()) {
+            return Err(Error::Validation(format!(
+                "answers table [{}] isn't named for a day, like [day1]",
+                key
+            )));
+        }
+        Ok(answers)
+    }
+
+    /// Reads an answers file.
+    pub fn load(path: &Path) -> Result<Answers> {
+        let contents = std::fs::read_to_string(path).map_err(|err| Error::io(path, err))?;
+        Answers::parse(&contents).map_err(|err| err.in_file(path))
+    }
+
+    /// Reads an answers file, or starts with no answers if it doesn't exist.
+    pub fn load_or_default(path: &Path) -> Result<Answers> {
+        match Answers::load(path) {
+            Err(Error::Io { source, .. }) if source.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Answers::default())
+            }
+            answers => answers,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_toml()).map_err(|err| Error::io(path, err))
+    }
+
+    /// The days with recorded answers, in order.
+    pub fn days(&self) -> Vec<u32> {
+        let mut days: Vec<u32> = self.days.keys().filter_map(|key| key_day(key)).collect();
+        days.sort_unstable();
+        days
+    }
+
+    /// The recorded answer for a day and part, if any.
+    pub fn expected(&self, day: u32, part: Part) -> Option<&Answer> {
+        let answers = self.days.get(&day_key(day))?;
+
+        match part {
+            Part::One => answers.part1.as_ref(),
+            Part::Two => answers.part2.as_ref(),
+        }
+    }
+
+    /// Records the answer for a day and part, replacing any previous one.
+    pub fn record(&mut self, day: u32, part: Part, answer: Answer) {
+        let answers = self.days.entry(day_key(day)).or_default();
+
+        match part {
+            Part::One => answers.part1 = Some(answer),
+            Part::Two => answers.part2 = Some(answer),
+        }
+    }
+
+    /// The TOML contents of an answers file.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("answers should serialize to TOML")
+    }
+
+    /// Compares `actual` against the recorded answer.
+    pub fn check(&self, day: u32, part: Part, actual: &Answer) -> Status {
+        match self.expected(day, part) {
+            Some(expected) if expected == actual => Status::Pass,
+            Some(expected) => Status::Fail {
+                expected: expected.clone(),
+                actual: actual.clone(),
+            },
+            None => Status::Missing,
+        }
+    }
+}
+
+impl Verification {
+    /// Whether the answer was wrong or couldn't be worked out.
+    pub fn failed(&self) -> bool {
+        matches!(self.status, Status::Fail { .. } | Status::Error { .. })
+    }
+}
+
+/// Solves both parts of every day in `days` with its input from `store`, and
+/// checks the answers against `answers`. A part with an answer but no
+/// readable input is an error; one without an answer is missing. Fails if
+/// `answers` has a day that isn't in `days`.
+pub fn verify(answers: &Answers, days: &[Day], store: &InputStore) -> Result<Vec<Verification>> {
+    if let Some(day) = answers
+        .days()
+        .into_iter()
+        .find(|&number| days.iter().all(|day| day.number != number))
+    {
+        return Err(Error::Validation(format!(
+            "answers are recorded for day {}, which has no solver",
+            day
+        )));
+    }
+
+    let mut verifications = Vec::new();
+    for day in days {
+        // Only days with answers need their input, which may be fetched.
+        let recorded = Part::ALL.iter().any(|&part| answers.expected(day.number, part).is_some());
+        let input = recorded.then(|| store.get(day.number));
+
+        for part in Part::ALL {
+            let status = match (&input, answers.expected(day.number, part)) {
+                (Some(Ok(input)), Some(_)) => match runner::run_part(day, input, part) {
+                    Ok(result) => answers.check(day.number, part, &result.answer),
+                    Err(err) => Status::Error {
+                        message: err.to_string(),
+                    },
+                },
+                (Some(Err(err)), Some(_)) => Status::Error {
+                    message: err.to_string(),
+                },
+                _ => Status::Missing,
+            };
+
+            verifications.push(Verification {
+                day: day.number,
+                part,
+                status,
+            });
+        }
+    }
+
+    Ok(verifications)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ANSWERS: &str = r#"
+[day1]
+part1 = 142
+part2 = 281
+
+[day4]
+part1 = 13
+"#;
+
+    #[test]
+    fn parse_answers() {
+        let answers = Answers::parse(ANSWERS).expect("answers should parse");
+
+        assert_eq!(answers.expected(1, Part::One), Some(&Answer::Number(142)));
+        assert_eq!(answers.expected(1, Part::Two), Some(&Answer::Number(281)));
+        assert_eq!(answers.expected(4, Part::One), Some(&Answer::Number(13)));
+        assert_eq!(answers.expected(4, Part::Two), None);
+        assert_eq!(answers.expected(2, Part::One), None);
+    }
+
+    #[test]
+    fn parse_text_answer() {
+        let answers = Answers::parse("[day5]\npart1 = \"ABC\"").expect("answers should parse");
+        assert_eq!(
+            answers.expected(5, Part::One),
+            Some(&Answer::Text("ABC".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_answers() {
+        match Answers::parse("[day1]\npart1 = 142\npart2 = \n") {
+            Err(Error::Parse { line, .. }) => assert_eq!(line, 3),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn answers_are_keyed_by_day() {
+        let answers = Answers::parse(ANSWERS).expect("answers should parse");
+        assert_eq!(answers.days(), vec![1, 4]);
+
+        for input in ["[part1]\npart1 = 1", "[day01]\npart1 = 1", "[dayx]\npart1 = 1"] {
+            assert!(matches!(Answers::parse(input), Err(Error::Validation(_))), "{}", input);
+        }
+    }
+
+    #[test]
+    fn check_answers() {
+        let answers = Answers::parse(ANSWERS).expect("answers should parse");
+
+        assert_eq!(
+            answers.check(1, Part::One, &Answer::Number(142)),
+            Status::Pass
+        );
+        assert_eq!(
+            answers.check(1, Part::Two, &Answer::Number(280)),
+            Status::Fail {
+                expected: Answer::Number(281),
+                actual: Answer::Number(280)
+            }
+        );
+        assert_eq!(
+            answers.check(4, Part::Two, &Answer::Number(30)),
+            Status::Missing
+        );
+    }
+
+    #[test]
+    fn record_answers() {
+        let mut answers = Answers::parse(ANSWERS).expect("answers should parse");
+        answers.record(4, Part::Two, Answer::Number(30));
+        answers.record(1, Part::One, Answer::Number(143));
+
+        let answers = Answers::parse(&answers.to_toml()).expect("answers should round-trip");
+        assert_eq!(answers.expected(4, Part::Two), Some(&Answer::Number(30)));
+        assert_eq!(answers.expected(1, Part::One), Some(&Answer::Number(143)));
+        assert_eq!(answers.expected(1, Part::Two), Some(&Answer::Number(281)));
+    }
+
+    #[test]
+    fn verification_json() {
+        let verification = Verification {
+            day: 1,
+            part: Part::Two,
+            status: Status::Fail {
+                expected: Answer::Number(281),
+                actual: Answer::Number(280),
+            },
+        };
+
+        assert_eq!(
+            serde_json::to_string(&verification).unwrap(),
+            r#"{"day":1,"part":2,"status":"fail","expected":281,"actual":280}"#
+        );
+    }
+    #[test]
+    fn load_and_save() {
+        let path = std::env::temp_dir().join("aoc_2023_answers_test.toml");
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(Answers::load(&path), Err(Error::Io { .. })));
+        assert_eq!(Answers::load_or_default(&path).unwrap(), Answers::default());
+
+        let answers = Answers::parse(ANSWERS).unwrap();
+        answers.save(&path).unwrap();
+        assert_eq!(Answers::load(&path).unwrap(), answers);
+
+        std::fs::write(&path, "[day1]\npart1 = ").unwrap();
+        assert!(matches!(Answers::load_or_default(&path), Err(Error::File { .. })));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn verify_days() {
+        let root = std::env::temp_dir().join("aoc_2023_verify");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("day1.txt"), "1abc2\npqr3stu8vwx").unwrap();
+        let store = InputStore::new(&root, 2023);
+        let days: Vec<Day> = [1, 3].into_iter().filter_map(crate::registry::find).collect();
+
+        let answers = Answers::parse("[day1]\npart1 = 50\npart2 = 51").unwrap();
+        let statuses: Vec<Status> = verify(&answers, &days, &store)
+            .unwrap()
+            .into_iter()
+            .map(|verification| verification.status)
+            .collect();
+        assert_eq!(statuses[0], Status::Pass);
+        assert!(matches!(statuses[1], Status::Fail { .. }));
+        assert_eq!(statuses[2..], [Status::Missing, Status::Missing]);
+
+        let answers = Answers::parse("[day3]\npart1 = 4361").unwrap();
+        let verifications = verify(&answers, &days, &store).unwrap();
+        assert!(matches!(verifications[2].status, Status::Error { .. }));
+        assert!(verifications[2].failed());
+        assert_eq!(verifications[3].status, Status::Missing);
+
+        let unknown = Answers::parse("[day9]\npart1 = 1").unwrap();
+        assert!(matches!(verify(&unknown, &days, &store), Err(Error::Validation(_))));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Repeated timing of a day's parse and solve stages.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::registry::Day;
+use crate::runner::serialize_millis;
+use crate::solver::Part;
+
+/// Summary of a set of timing samples.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Stats {
+    #[serde(rename = "min_ms", serialize_with = "serialize_millis")]
+    pub min: Duration,
+    #[serde(rename = "median_ms", serialize_with = "serialize_millis")]
+    pub median: Duration,
+    #[serde(rename = "max_ms", serialize_with = "serialize_millis")]
+    pub max: Duration,
+}
+
+/// Timings for each stage of a day over several runs.
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub day: u32,
+    pub runs: usize,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl Stats {
+    /// Returns `None` when there are no samples.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Option<Stats> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        samples.sort();
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+
+        Some(Stats {
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        })
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> Result<T>) -> Result<(T, Duration)> {
+    let start = Instant::now();
+    let value = f()?;
+    Ok((value, start.elapsed()))
+}
+
+/// Parses and solves `input` `runs` times, timing parsing and each part
+/// separately.
+pub fn bench(day: &Day, input: &str, runs: usize) -> Result<BenchReport> {
+    if runs == 0 {
+        return Err(Error::Validation("bench needs at least one run".to_string()));
+    }
+
+    let mut parse_samples = Vec::with_capacity(runs);
+    let mut part1_samples = Vec::with_capacity(runs);
+    let mut part2_samples = Vec::with_capacity(runs);
+
+    for _ in 0..runs {
+        let (parsed, parse_time) = timed(|| day.solver.parse_input(input))?;
+        let (_, part1_time) = timed(|| day.solver.solve_part(parsed.as_ref(), Part::One))?;
+        let (_, part2_time) = timed(|| day.solver.solve_part(parsed.as_ref(), Part::Two))?;
+
+        parse_samples.push(parse_time);
+        part1_samples.push(part1_time);
+        part2_samples.push(part2_time);
+    }
+
+    Ok(BenchReport {
+        day: day.number,
+        runs,
+        parse: Stats::from_samples(parse_samples).expect("runs should be non-zero"),
+        part1: Stats::from_samples(part1_samples).expect("runs should be non-zero"),
+        part2: Stats::from_samples(part2_samples).expect("runs should be non-zero"),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn stats_odd_samples() {
+        let samples = vec![
+            Duration::from_millis(5),
+            Duration::from_millis(1),
+            Duration::from_millis(3),
+        ];
+
+        assert_eq!(
+            Stats::from_samples(samples),
+            Some(Stats {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(3),
+                max: Duration::from_millis(5),
+            })
+        );
+    }
+
+    #[test]
+    fn stats_even_samples() {
+        let samples = vec![
+            Duration::from_millis(4),
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+            Duration::from_millis(8),
+        ];
+
+        let stats = Stats::from_samples(samples).unwrap();
+        assert_eq!(stats.median, Duration::from_millis(3));
+        assert_eq!(Stats::from_samples(Vec::new()), None);
+    }
+
+    #[test]
+    fn bench_day() {
+        let day = registry::find(1).expect("day 1 should be registered");
+        let report = bench(&day, "1abc2\npqr3stu8vwx", 3).unwrap();
+
+        assert_eq!(report.day, 1);
+        assert_eq!(report.runs, 3);
+        assert!(report.parse.min <= report.parse.median);
+        assert!(report.part2.median <= report.part2.max);
+    }
+
+    #[test]
+    fn bench_reports_parse_errors() {
+        let day = registry::find(4).expect("day 4 should be registered");
+        assert!(bench(&day, "Card x", 3).is_err());
+        assert!(bench(&day, "Card 1: 1 | 1", 0).is_err());
+    }
+}
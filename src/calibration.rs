@@ -1,57 +1,665 @@
-use regex::Regex;
-
-fn parse_value(value: &str) -> Option<i32> {
-    match value {
-        "zero" => Some(0),
-        "one" => Some(1),
-        "two" => Some(2),
-        "three" => Some(3),
-        "four" => Some(4),
-        "five" => Some(5),
-        "six" => Some(6),
-        "seven" => Some(7),
-        "eight" => Some(8),
-        "nine" => Some(9),
-        numeric => numeric.parse::<i32>().ok(),
-    }
-}
-
-fn value_for_line(line: String) -> Option<i32> {
-    let re: Regex =
-        Regex::new(r"([0-9]|zero|one|two|three|four|five|six|seven|eight|nine)").unwrap();
-
-    let mut line = line;
-    let mut numbers = Vec::<i32>::new();
-
-    while !line.is_empty() {
-        if let Some(number) = re.find(&line) {
-            if let Some(number) = parse_value(number.as_str()) {
-                numbers.push(number);
+//! Day 1: Trebuchet?!
+//!
+//! Each line of the calibration document hides a value made of its first and
+//! last digit. Part 1 only counts the numerals 0-9; part 2 also counts digits
+//! spelled out as words (see [`Mode`]). The words come from a
+//! [`DigitVocabulary`], English unless another is chosen, and the digits may
+//! be combined by another rule than first and last (see [`Combine`]).
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use aho_corasick::AhoCorasick;
+use aho_corasick::MatchKind;
+use clap::ValueEnum;
+use log::trace;
+use serde::Serialize;
+
+use crate::error::numbered_lines;
+use crate::error::try_map_lines;
+use crate::error::Error;
+use crate::error::Result;
+use crate::parallel;
+use crate::solver::Explanation;
+use crate::solver::Part;
+use crate::solver::Solver;
+
+const NUMERALS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+const ENGLISH: [&str; 10] =
+    ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+const GERMAN: [&str; 10] =
+    ["null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun"];
+const FRENCH: [&str; 10] =
+    ["zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf"];
+const SPANISH: [&str; 10] =
+    ["cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve"];
+// There's no roman numeral for zero.
+const ROMAN: [&str; 10] = ["", "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX"];
+
+/// Which tokens count as digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Only the numerals 0-9, as in part 1.
+    Digits,
+    /// Numerals and digit words, as in part 2.
+    DigitsAndWords,
+}
+
+impl Mode {
+    pub fn for_part(part: Part) -> Mode {
+        match part {
+            Part::One => Mode::Digits,
+            Part::Two => Mode::DigitsAndWords,
+        }
+    }
+}
+
+/// The built-in vocabularies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+    Roman,
+}
+
+/// The words that stand for digits, recognized alongside the numerals 0-9.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DigitVocabulary {
+    // Each word and the digit it stands for.
+    words: Vec<(String, i32)>,
+    // Whether words may share letters, like the `eight` and `two` in
+    // `eightwo`. Otherwise the longest word wins, so `VIII` is just 8.
+    overlapping: bool,
+}
+
+impl DigitVocabulary {
+    /// A vocabulary where `words[d]` stands for the digit `d`. Empty words
+    /// leave their digit without one. Words can't repeat, contain numerals,
+    /// or start another word, since a line could then be read two ways.
+    pub fn from_words(words: &[&str]) -> Result<DigitVocabulary> {
+        DigitVocabulary::build(words, true)
+    }
+
+    // Words may only start other words when they can't overlap, as then the
+    // longest one wins.
+    fn build(words: &[&str], overlapping: bool) -> Result<DigitVocabulary> {
+        if words.len() > 10 {
+            return Err(Error::Validation(format!(
+                "{} digit words given, but there are only 10 digits",
+                words.len()
+            )));
+        }
+
+        let words: Vec<(String, i32)> = (0..)
+            .zip(words)
+            .filter(|(_, word)| !word.is_empty())
+            .map(|(digit, word)| (word.to_string(), digit))
+            .collect();
+        for (i, (word, _)) in words.iter().enumerate() {
+            if word.chars().any(|c| c.is_ascii_digit()) {
+                return Err(Error::Validation(format!(
+                    "digit word {:?} contains a numeral",
+                    word
+                )));
+            }
+            for (other, _) in &words[i + 1..] {
+                if word == other {
+                    return Err(Error::Validation(format!(
+                        "digit word {:?} is given twice",
+                        word
+                    )));
+                }
+                let (short, long) = if word.len() < other.len() {
+                    (word, other)
+                } else {
+                    (other, word)
+                };
+                if overlapping && long.starts_with(short.as_str()) {
+                    return Err(Error::Validation(format!(
+                        "digit word {:?} starts {:?}",
+                        short, long
+                    )));
+                }
+            }
+        }
+
+        Ok(DigitVocabulary { words, overlapping })
+    }
+
+    /// Parses a comma-separated word list, such as `nul,een,twee`, where the
+    /// first word stands for 0.
+    pub fn parse(list: &str) -> Result<DigitVocabulary> {
+        let words: Vec<&str> = list.split(',').map(str::trim).collect();
+        DigitVocabulary::from_words(&words)
+    }
+
+    /// A vocabulary without any words, so only numerals count.
+    pub fn numerals() -> DigitVocabulary {
+        DigitVocabulary {
+            words: Vec::new(),
+            overlapping: true,
+        }
+    }
+
+    pub fn english() -> DigitVocabulary {
+        DigitVocabulary::language(Language::English)
+    }
+
+    pub fn language(language: Language) -> DigitVocabulary {
+        let words = match language {
+            Language::English => ENGLISH,
+            Language::German => GERMAN,
+            Language::French => FRENCH,
+            Language::Spanish => SPANISH,
+            Language::Roman => ROMAN,
+        };
+
+        DigitVocabulary::build(&words, language != Language::Roman)
+            .expect("built-in vocabularies are valid")
+    }
+}
+
+impl Default for DigitVocabulary {
+    fn default() -> DigitVocabulary {
+        DigitVocabulary::english()
+    }
+}
+
+/// A custom rule for [`Combine::Custom`].
+pub type CombineFn = dyn Fn(&[i32]) -> Option<i32> + Send + Sync;
+
+/// How the digits found on a line are combined into its value.
+#[derive(Clone, Default)]
+pub enum Combine {
+    /// The first digit followed by the last, as in the puzzle.
+    #[default]
+    FirstLast,
+    /// The first `n` digits followed by the last `n`. Lines with fewer than
+    /// `n` digits use all of them for both. `n` must be at least 1.
+    FirstLastN(usize),
+    /// Every digit in order, read as one number.
+    All,
+    /// The sum of the digits.
+    Sum,
+    /// Any other rule, given every digit. Returning `None` rejects the line.
+    Custom(Arc<CombineFn>),
+}
+
+// Reads `digits` as the decimal number they spell, if it fits.
+fn concat(digits: &[i32]) -> Option<i32> {
+    digits
+        .iter()
+        .try_fold(0i32, |number, &digit| number.checked_mul(10)?.checked_add(digit))
+}
+
+impl Combine {
+    /// The first `n` digits followed by the last `n`, rejecting `n == 0`.
+    pub fn first_last_n(n: usize) -> Result<Combine> {
+        if n == 0 {
+            return Err(Error::Validation("first-last:0 uses no digits".to_string()));
+        }
+        Ok(Combine::FirstLastN(n))
+    }
+
+    /// Which of `count` digits make up the value, by index, in the order
+    /// they're used. An index can appear twice, as with a single digit under
+    /// [`Combine::FirstLast`].
+    pub fn used(&self, count: usize) -> Vec<usize> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        match self {
+            Combine::FirstLast => vec![0, count - 1],
+            Combine::FirstLastN(n) => {
+                let n = (*n).min(count);
+                (0..n).chain(count - n..count).collect()
             }
+            Combine::All | Combine::Sum | Combine::Custom(_) => (0..count).collect(),
+        }
+    }
+
+    /// The value of a line with these digits. Fails if there are none, if
+    /// `n` is 0 for [`Combine::FirstLastN`], if a custom rule rejects them,
+    /// or if the value doesn't fit in an `i32`.
+    pub fn apply(&self, digits: &[i32]) -> Result<i32> {
+        let invalid = |message: &str| Error::Validation(message.to_string());
+        if digits.is_empty() {
+            return Err(invalid("no digits to combine"));
+        }
+        if let Combine::FirstLastN(0) = self {
+            return Err(invalid("first-last:0 uses no digits"));
+        }
+
+        let used: Vec<i32> = self.used(digits.len()).iter().map(|&i| digits[i]).collect();
+        let value = match self {
+            Combine::Sum => used.iter().try_fold(0i32, |sum, &digit| sum.checked_add(digit)),
+            Combine::Custom(combine) => {
+                return combine(&used).ok_or_else(|| invalid("the custom rule rejected the digits"))
+            }
+            _ => concat(&used),
+        };
+        value.ok_or_else(|| invalid("the value is too large"))
+    }
+}
+
+impl fmt::Debug for Combine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Combine::FirstLast => f.write_str("FirstLast"),
+            Combine::FirstLastN(n) => f.debug_tuple("FirstLastN").field(n).finish(),
+            Combine::All => f.write_str("All"),
+            Combine::Sum => f.write_str("Sum"),
+            Combine::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Parses `first-last`, `first-last:<n>`, `all` or `sum`.
+impl FromStr for Combine {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "first-last" => Ok(Combine::FirstLast),
+            "all" => Ok(Combine::All),
+            "sum" => Ok(Combine::Sum),
+            other => match other.strip_prefix("first-last:").map(str::parse) {
+                Some(Ok(n)) if n > 0 => Combine::first_last_n(n).map_err(|err| err.to_string()),
+                _ => Err(format!(
+                    "expected first-last, first-last:<n>, all or sum, got {}",
+                    other
+                )),
+            },
+        }
+    }
+}
+
+/// Finds numerals and digit words in a single pass over each line. Build one
+/// per corpus and reuse it for every line.
+pub struct DigitMatcher {
+    automaton: AhoCorasick,
+    // The digit each pattern stands for, by pattern index.
+    values: Vec<i32>,
+    overlapping: bool,
+    combine: Combine,
+}
+
+impl DigitMatcher {
+    pub fn new(vocabulary: &DigitVocabulary) -> DigitMatcher {
+        let numerals = (0..).zip(NUMERALS).map(|(digit, numeral)| (numeral, digit));
+        let words = vocabulary.words.iter().map(|(word, digit)| (word.as_str(), *digit));
+        let (patterns, values): (Vec<&str>, Vec<i32>) = numerals.chain(words).unzip();
+
+        let kind = if vocabulary.overlapping {
+            MatchKind::Standard
+        } else {
+            MatchKind::LeftmostLongest
+        };
+
+        DigitMatcher {
+            automaton: AhoCorasick::builder()
+                .match_kind(kind)
+                .build(patterns)
+                .expect("digit patterns should compile"),
+            values,
+            overlapping: vocabulary.overlapping,
+            combine: Combine::FirstLast,
         }
-        line.drain(..1);
     }
 
-    if let Some(first_number) = numbers.first() {
-        if let Some(last_number) = numbers.last() {
-            let number = format!("{}{}", first_number, last_number);
-            return number.parse::<i32>().ok();
+    pub fn with_combine(mut self, combine: Combine) -> DigitMatcher {
+        self.combine = combine;
+        self
+    }
+
+    /// A matcher for `mode`, using `vocabulary` when words count.
+    pub fn for_mode(vocabulary: &DigitVocabulary, mode: Mode) -> DigitMatcher {
+        match mode {
+            Mode::Digits => DigitMatcher::new(&DigitVocabulary::numerals()),
+            Mode::DigitsAndWords => DigitMatcher::new(vocabulary),
+        }
+    }
+
+    /// Every numeral and digit word in `line` in order of where it starts,
+    /// including overlapping words like the `one` and `eight` in `oneight`
+    /// when the vocabulary allows them.
+    pub fn tokens(&self, line: &str) -> Vec<Token> {
+        let matches: Vec<_> = if self.overlapping {
+            self.automaton.find_overlapping_iter(line).collect()
+        } else {
+            self.automaton.find_iter(line).collect()
+        };
+
+        let mut tokens: Vec<Token> = matches
+            .iter()
+            .map(|m| Token {
+                start: m.start(),
+                end: m.end(),
+                digit: self.values[m.pattern().as_usize()],
+            })
+            .collect();
+        // Overlapping matches come out in order of where they end.
+        tokens.sort_by_key(|token| token.start);
+        tokens
+    }
+
+    /// The digits of [`DigitMatcher::tokens`].
+    pub fn digits(&self, line: &str) -> Vec<i32> {
+        self.tokens(line).iter().map(|token| token.digit).collect()
+    }
+
+    /// One line of a corpus and every token in it.
+    pub fn scan_line(&self, line_number: usize, line: &str) -> ScannedLine {
+        ScannedLine {
+            line: line_number,
+            text: line.to_string(),
+            tokens: self.tokens(line),
         }
     }
 
-    None
+    /// Scans every non-blank line of a corpus, rejecting lines without a
+    /// single numeral or digit word.
+    pub fn scan(&self, corpus: &str) -> Result<Vec<ScannedLine>> {
+        try_map_lines(corpus, |line_number, _, line| {
+            let scanned = self.scan_line(line_number, line);
+            if scanned.tokens.is_empty() {
+                return Err(missing_digits(line_number, line));
+            }
+            Ok(scanned)
+        })
+    }
+
+    /// Everything matched in one line of a corpus.
+    pub fn report_line(&self, line_number: usize, line: &str) -> LineReport {
+        let tokens = self.tokens(line);
+        let digits: Vec<i32> = tokens.iter().map(|token| token.digit).collect();
+        let value = self.combine.apply(&digits).ok();
+        let used = match value {
+            Some(_) => self.combine.used(tokens.len()).iter().map(|&i| tokens[i]).collect(),
+            None => Vec::new(),
+        };
+
+        LineReport {
+            line: line_number,
+            text: line.to_string(),
+            tokens,
+            used,
+            value,
+        }
+    }
+
+    /// A [`LineReport`] for every non-blank line. Lines without digits are
+    /// reported rather than rejected.
+    pub fn report(&self, corpus: &str) -> Vec<LineReport> {
+        numbered_lines(corpus)
+            .map(|(line_number, _, line)| self.report_line(line_number, line))
+            .collect()
+    }
+
+    /// The digits of `line` combined into its value, if they combine.
+    pub fn value_for_line(&self, line: &str) -> Option<i32> {
+        self.combine.apply(&self.digits(line)).ok()
+    }
+
+    /// Sums the calibration value of every non-blank line. A line without
+    /// any digit, or whose digits don't combine, is rejected, as is a total
+    /// too large for an `i32`.
+    pub fn value_for_corpus(&self, corpus: &str) -> Result<i32> {
+        total(&self.scan(corpus)?, Mode::DigitsAndWords, &self.combine)
+    }
+
+    /// The digits used and the value of every non-blank line.
+    pub fn line_values(&self, corpus: &str) -> Result<Vec<LineValue>> {
+        line_values_of(&self.scan(corpus)?, Mode::DigitsAndWords, &self.combine)
+    }
+}
+
+/// One non-blank line of the document and the tokens found in it.
+#[derive(Debug, PartialEq)]
+pub struct ScannedLine {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The line, trimmed. Token offsets are into this.
+    pub text: String,
+    pub tokens: Vec<Token>,
 }
 
-pub fn value_for_corpus(corpus: &str) -> i32 {
-    let mut calibrations = Vec::<i32>::new();
+impl ScannedLine {
+    fn is_numeral(&self, token: &Token) -> bool {
+        NUMERALS.contains(&&self.text[token.start..token.end])
+    }
+
+    /// The digits `mode` counts, in order.
+    pub fn digits(&self, mode: Mode) -> Vec<i32> {
+        self.tokens
+            .iter()
+            .filter(|token| mode == Mode::DigitsAndWords || self.is_numeral(token))
+            .map(|token| token.digit)
+            .collect()
+    }
 
-    for line in corpus.lines() {
-        if let Some(calibration) = value_for_line(line.into()) {
-            calibrations.push(calibration);
+    // The digits `combine` uses, in order, and their combined value. A line
+    // without any digits `mode` counts is rejected.
+    fn combined(&self, mode: Mode, combine: &Combine) -> Result<(Vec<i32>, i32)> {
+        let digits = self.digits(mode);
+        if digits.is_empty() {
+            return Err(missing_digits(self.line, &self.text));
         }
+
+        let value = combine.apply(&digits).map_err(|err| match err {
+            Error::Validation(message) => {
+                Error::Validation(format!("line {}: {}: {:?}", self.line, message, self.text))
+            }
+            other => other,
+        })?;
+        let used = combine.used(digits.len()).iter().map(|&i| digits[i]).collect();
+        Ok((used, value))
     }
 
-    calibrations.iter().sum::<i32>()
+    /// The line's calibration value.
+    pub fn value(&self, mode: Mode, combine: &Combine) -> Result<i32> {
+        self.combined(mode, combine).map(|(_, value)| value)
+    }
+}
+
+/// Sums the values of scanned lines, rejecting a total too large for an
+/// `i32`.
+pub fn total(lines: &[ScannedLine], mode: Mode, combine: &Combine) -> Result<i32> {
+    let values = parallel::map(lines, |line| line.value(mode, combine));
+
+    let mut total = 0i32;
+    for (line, value) in lines.iter().zip(values) {
+        let value = value?;
+        trace!("line {}: {:?} is worth {}", line.line, line.text, value);
+        total = total
+            .checked_add(value)
+            .ok_or_else(|| Error::Validation("the calibration total is too large".to_string()))?;
+    }
+
+    Ok(total)
+}
+
+fn line_values_of(lines: &[ScannedLine], mode: Mode, combine: &Combine) -> Result<Vec<LineValue>> {
+    lines
+        .iter()
+        .map(|line| {
+            let (digits, value) = line.combined(mode, combine)?;
+            Ok(LineValue {
+                line: line.line,
+                digits,
+                value,
+            })
+        })
+        .collect()
+}
+
+impl Default for DigitMatcher {
+    fn default() -> DigitMatcher {
+        DigitMatcher::new(&DigitVocabulary::english())
+    }
+}
+
+fn missing_digits(line_number: usize, line: &str) -> Error {
+    Error::Validation(format!(
+        "line {}: no calibration digits in {:?}",
+        line_number, line
+    ))
+}
+
+/// Sums the calibration value of every non-blank line, counting the digits
+/// `mode` allows, with words in English. A line without any digit is
+/// rejected.
+pub fn value_for_corpus(corpus: &str, mode: Mode) -> Result<i32> {
+    DigitMatcher::for_mode(&DigitVocabulary::english(), mode).value_for_corpus(corpus)
+}
+
+/// A numeral or digit word found in a line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Token {
+    /// Byte offset of the token in the (trimmed) line.
+    pub start: usize,
+    /// Byte offset just past the token.
+    pub end: usize,
+    pub digit: i32,
+}
+
+// Terminal colors for `LineReport::highlighted`, which also tell
+// `LineReport::bracketed` how to mark a run.
+const CHOSEN: &str = "\x1b[1;32m";
+const MATCHED: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Every token matched in one line, the ones the value is made from, and
+/// the value.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LineReport {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The line, trimmed. Token offsets are into this.
+    pub text: String,
+    pub tokens: Vec<Token>,
+    /// The tokens the value is made from, in the order the [`Combine`] rule
+    /// uses them.
+    pub used: Vec<Token>,
+    /// `None` when the line's digits don't combine, such as when it has
+    /// none.
+    pub value: Option<i32>,
+}
+
+impl LineReport {
+    /// The text a token matched.
+    pub fn matched(&self, token: &Token) -> &str {
+        &self.text[token.start..token.end]
+    }
+
+    /// The line with ANSI colors: the tokens the value uses in bold green and
+    /// any other tokens in yellow. Where tokens overlap, a used one wins.
+    pub fn highlighted(&self) -> String {
+        self.marked(|style, run| format!("{}{}{}", style, run, RESET))
+    }
+
+    /// The line without colors, for when they can't be shown: the tokens the
+    /// value uses in `[]` and any other tokens in `()`.
+    pub fn bracketed(&self) -> String {
+        self.marked(|style, run| match style {
+            CHOSEN => format!("[{}]", run),
+            _ => format!("({})", run),
+        })
+    }
+
+    // The line with each run of used or other matched text passed through
+    // `mark` along with its color.
+    fn marked(&self, mark: impl Fn(&str, &str) -> String) -> String {
+        let mut styles = vec![None; self.text.len()];
+        for token in &self.tokens {
+            let style = if self.used.contains(token) { CHOSEN } else { MATCHED };
+            for byte_style in &mut styles[token.start..token.end] {
+                if *byte_style != Some(CHOSEN) {
+                    *byte_style = Some(style);
+                }
+            }
+        }
+
+        // Styles only change at token boundaries, which are char boundaries.
+        let mut marked = String::new();
+        let mut run_start = 0;
+        for end in 1..=self.text.len() {
+            if end < self.text.len() && styles[end] == styles[run_start] {
+                continue;
+            }
+            let run = &self.text[run_start..end];
+            match styles[run_start] {
+                Some(style) => marked.push_str(&mark(style, run)),
+                None => marked.push_str(run),
+            }
+            run_start = end;
+        }
+
+        marked
+    }
+}
+
+/// How one line of the document contributes to the total.
+#[derive(Debug, PartialEq)]
+pub struct LineValue {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The digits the value is made from, in the order they're used.
+    pub digits: Vec<i32>,
+    pub value: i32,
+}
+
+/// The digits used and the value of every non-blank line, reading digit
+/// words in English.
+pub fn line_values(corpus: &str) -> Result<Vec<LineValue>> {
+    DigitMatcher::default().line_values(corpus)
+}
+
+/// Day 1 solver. Digit words only count in part 2 and are read in English
+/// unless another vocabulary is given. Each line is worth its first and last
+/// digit unless another [`Combine`] rule is given.
+#[derive(Default)]
+pub struct Day1 {
+    pub vocabulary: DigitVocabulary,
+    pub combine: Combine,
+}
+
+// Parsing scans each line for numerals and words once, and rejects lines
+// with neither; part 1 then ignores the words.
+impl Solver for Day1 {
+    type Parsed = Vec<ScannedLine>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, input: &str) -> Result<Vec<ScannedLine>> {
+        DigitMatcher::new(&self.vocabulary).scan(input)
+    }
+
+    fn part1(&self, lines: &Vec<ScannedLine>) -> Result<i32> {
+        total(lines, Mode::Digits, &self.combine)
+    }
+
+    fn part2(&self, lines: &Vec<ScannedLine>) -> Result<i32> {
+        total(lines, Mode::DigitsAndWords, &self.combine)
+    }
+
+    fn explain(&self, lines: &Vec<ScannedLine>, part: Part) -> Result<Explanation> {
+        let mut explanation = Explanation::new(&["line", "digits", "value"]);
+        for value in line_values_of(lines, Mode::for_part(part), &self.combine)? {
+            let digits: Vec<String> = value.digits.iter().map(|d| d.to_string()).collect();
+            explanation.push_row(vec![
+                value.line.to_string(),
+                digits.join(" "),
+                value.value.to_string(),
+            ]);
+        }
+        Ok(explanation)
+    }
 }
 
 #[cfg(test)]
@@ -67,7 +675,7 @@ mod test {
         treb7uchet
         ";
 
-        let calibration = value_for_corpus(&example);
+        let calibration = value_for_corpus(example, Mode::Digits).unwrap();
         assert_eq!(calibration, 142);
     }
 
@@ -83,12 +691,75 @@ mod test {
         7pqrstsixteen
         ";
 
-        let calibration = value_for_corpus(&example);
+        let calibration = value_for_corpus(example, Mode::DigitsAndWords).unwrap();
         assert_eq!(calibration, 281);
     }
 
+    #[test]
+    fn day1_modes_on_mixed_input() {
+        let example = "
+        two1nine
+        eightwothree3
+        x4oneight
+        7pqrstsixteen
+        ";
+
+        // 11 + 33 + 44 + 77 counting numerals only, 29 + 83 + 48 + 76 with words.
+        assert_eq!(value_for_corpus(example, Mode::Digits).unwrap(), 165);
+        assert_eq!(value_for_corpus(example, Mode::DigitsAndWords).unwrap(), 236);
+
+        let day = Day1::default();
+        assert_eq!(day.part1(&day.parse(example).unwrap()).unwrap(), 165);
+        assert_eq!(day.part2(&day.parse(example).unwrap()).unwrap(), 236);
+    }
+
+    #[test]
+    fn day1_parse_scans_lines() {
+        let lines = Day1::default().parse("\n  x2one\n").unwrap();
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!((lines[0].line, lines[0].text.as_str()), (2, "x2one"));
+        assert_eq!(lines[0].digits(Mode::Digits), vec![2]);
+        assert_eq!(lines[0].digits(Mode::DigitsAndWords), vec![2, 1]);
+    }
+
+    #[test]
+    fn day1_parse_rejects_lines_without_digits() {
+        let day = Day1::default();
+        assert!(day.parse("eightwothree\n1abc").is_ok());
+        match day.parse("1abc\nhello world") {
+            Err(Error::Validation(message)) => assert!(message.starts_with("line 2:")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn day1_explain_follows_part() {
+        let day = Day1::default();
+        let lines = day.parse("two1nine").unwrap();
+        let value = |part| day.explain(&lines, part).unwrap().to_string();
+
+        assert!(value(Part::One).ends_with("1     1 1     11\n"));
+        assert!(value(Part::Two).ends_with("1     2 9     29\n"));
+    }
+
+    #[test]
+    fn day1_words_only_line_needs_words() {
+        assert!(value_for_corpus("one", Mode::DigitsAndWords).is_ok());
+        match value_for_corpus("1\none", Mode::Digits) {
+            Err(Error::Validation(message)) => assert!(message.starts_with("line 2:")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
     #[test]
     fn day1_test_value_parsing() {
+        let matcher = DigitMatcher::default();
+        let parse_value = |token: &str| match matcher.tokens(token)[..] {
+            [only] if only.end - only.start == token.len() => Some(only.digit),
+            _ => None,
+        };
+
         assert_eq!(parse_value("zero"), Some(0));
         assert_eq!(parse_value("one"), Some(1));
         assert_eq!(parse_value("two"), Some(2));
@@ -113,17 +784,223 @@ mod test {
     }
 
     #[test]
-    fn day1_solution_debug() {
+    fn day1_line_values() {
+        let example = "
+        two1nine
+        zoneight234
+        ";
+
         assert_eq!(
-            value_for_line("jvhhrkrnhfivenineonethree3sixninegplzthbxj".into()),
-            Some(59)
+            line_values(example).unwrap(),
+            vec![
+                LineValue { line: 2, digits: vec![2, 9], value: 29 },
+                LineValue { line: 3, digits: vec![1, 4], value: 14 },
+            ]
         );
+        assert!(line_values("abc").is_err());
+    }
+
+    #[test]
+    fn day1_line_without_digits() {
+        let example = "
+        1abc2
+        pqrstuvwx
+        ";
+
+        match value_for_corpus(example, Mode::DigitsAndWords) {
+            Err(Error::Validation(message)) => assert!(message.starts_with("line 3:")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn day1_solution_debug() {
+        let matcher = DigitMatcher::default();
+        let value_for_line = |line: &str| matcher.value_for_line(line);
+
+        assert_eq!(value_for_line("jvhhrkrnhfivenineonethree3sixninegplzthbxj"), Some(59));
+        assert_eq!(value_for_line("qkqgptwotvjkctgsbmsxvmssdpteightlxlkfqv46"), Some(26));
+        assert_eq!(value_for_line("one"), Some(11));
+        assert_eq!(value_for_line("sevenine"), Some(79));
+        assert_eq!(value_for_line(""), None);
+    }
+
+    #[test]
+    fn day1_overlapping_digits() {
+        let matcher = DigitMatcher::default();
+        assert_eq!(matcher.digits("eightwothree"), vec![8, 2, 3]);
+        assert_eq!(matcher.digits("xtwone3four"), vec![2, 1, 3, 4]);
+        assert_eq!(matcher.digits("oneightwoneight"), vec![1, 8, 2, 1, 8]);
+    }
+
+    #[test]
+    fn day1_long_line() {
+        let line = format!("x{}x", "twone".repeat(200_000));
+        assert_eq!(DigitMatcher::default().value_for_line(&line), Some(21));
+    }
+
+    #[test]
+    fn day1_line_report() {
+        let report = DigitMatcher::default().report_line(4, "xtwone3");
+
+        let spans: Vec<_> = report.tokens.iter().map(|t| (t.start, t.end, t.digit)).collect();
+        assert_eq!(spans, vec![(1, 4, 2), (3, 6, 1), (6, 7, 3)]);
+        let used: Vec<_> = report.used.iter().map(|token| report.matched(token)).collect();
+        assert_eq!(used, vec!["two", "3"]);
+        assert_eq!(report.value, Some(23));
+
         assert_eq!(
-            value_for_line("qkqgptwotvjkctgsbmsxvmssdpteightlxlkfqv46".into()),
-            Some(26)
+            report.highlighted(),
+            "x\x1b[1;32mtwo\x1b[0m\x1b[33mne\x1b[0m\x1b[1;32m3\x1b[0m"
         );
-        assert_eq!(value_for_line("one".into()), Some(11));
-        assert_eq!(value_for_line("sevenine".into()), Some(79));
-        assert_eq!(value_for_line("".into()), None);
+        assert_eq!(report.bracketed(), "x[two](ne)[3]");
+    }
+
+    #[test]
+    fn day1_report_keeps_lines_without_digits() {
+        let reports = DigitMatcher::default().report("
+        abc
+        fünf1
+        ");
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!((reports[0].line, reports[0].value), (2, None));
+        assert!(reports[0].used.is_empty());
+        assert_eq!(reports[0].highlighted(), "abc");
+        assert_eq!(reports[0].bracketed(), "abc");
+        assert_eq!(reports[1].value, Some(11));
+        assert_eq!(reports[1].highlighted(), "fünf\x1b[1;32m1\x1b[0m");
+    }
+
+    #[test]
+    fn day1_combine_rules() {
+        let digits = [1, 2, 3, 4, 5];
+        assert_eq!(Combine::FirstLast.apply(&digits).ok(), Some(15));
+        assert_eq!(Combine::FirstLastN(2).apply(&digits).ok(), Some(1245));
+        assert_eq!(Combine::FirstLastN(1).apply(&digits).ok(), Some(15));
+        assert_eq!(Combine::FirstLastN(3).apply(&[7, 8]).ok(), Some(7878));
+        assert_eq!(Combine::All.apply(&digits).ok(), Some(12345));
+        assert_eq!(Combine::Sum.apply(&digits).ok(), Some(15));
+        assert!(matches!(Combine::Sum.apply(&[]), Err(Error::Validation(_))));
+
+        let max = Combine::Custom(Arc::new(|digits| digits.iter().copied().max()));
+        assert_eq!(max.apply(&[3, 9, 2]).ok(), Some(9));
+        assert!(Combine::Custom(Arc::new(|_| None)).apply(&digits).is_err());
+    }
+
+    #[test]
+    fn day1_combine_needs_digits_to_use() {
+        assert!(matches!(Combine::first_last_n(0), Err(Error::Validation(_))));
+        assert!(matches!(Combine::first_last_n(2), Ok(Combine::FirstLastN(2))));
+        assert!(matches!(Combine::FirstLastN(0).apply(&[1, 2]), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn day1_combine_used_digits() {
+        assert_eq!(Combine::FirstLast.used(1), vec![0, 0]);
+        assert_eq!(Combine::FirstLast.used(4), vec![0, 3]);
+        assert_eq!(Combine::FirstLastN(2).used(5), vec![0, 1, 3, 4]);
+        assert_eq!(Combine::Sum.used(3), vec![0, 1, 2]);
+        assert!(Combine::All.used(0).is_empty());
+
+        let matcher = DigitMatcher::default().with_combine(Combine::All);
+        let report = matcher.report_line(1, "x4oneight");
+        assert_eq!(report.used, report.tokens);
+        assert_eq!(report.highlighted(), "x\x1b[1;32m4oneight\x1b[0m");
+        assert_eq!(matcher.line_values("x4oneight").unwrap()[0].digits, vec![4, 1, 8]);
+    }
+
+    #[test]
+    fn day1_combine_overflow_is_rejected() {
+        assert_eq!(Combine::All.apply(&[2, 1, 4, 7, 4, 8, 3, 6, 4, 7]).ok(), Some(i32::MAX));
+        assert!(Combine::All.apply(&[2, 1, 4, 7, 4, 8, 3, 6, 4, 8]).is_err());
+
+        let matcher = DigitMatcher::default().with_combine(Combine::All);
+        match matcher.value_for_corpus("12\n99999999999") {
+            Err(Error::Validation(message)) => assert!(message.starts_with("line 2:")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn day1_total_overflow_is_rejected() {
+        let matcher = DigitMatcher::default().with_combine(Combine::All);
+        assert_eq!(matcher.value_for_corpus("2147483646\n1").unwrap(), i32::MAX);
+        assert!(matches!(
+            matcher.value_for_corpus("2147483647\n1"),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn day1_combine_in_corpus() {
+        let example = "
+        two1nine
+        x4oneight
+        ";
+
+        let sum = DigitMatcher::default().with_combine(Combine::Sum);
+        assert_eq!(sum.value_for_corpus(example).unwrap(), 12 + 13);
+        assert_eq!(sum.report_line(1, "two1nine").value, Some(12));
+
+        let day = Day1 { combine: Combine::All, ..Day1::default() };
+        assert_eq!(day.part1(&day.parse(example).unwrap()).unwrap(), 1 + 4);
+        assert_eq!(day.part2(&day.parse(example).unwrap()).unwrap(), 219 + 418);
+    }
+
+    #[test]
+    fn parse_combine() {
+        assert!(matches!("first-last".parse(), Ok(Combine::FirstLast)));
+        assert!(matches!("first-last:3".parse(), Ok(Combine::FirstLastN(3))));
+        assert!(matches!("sum".parse(), Ok(Combine::Sum)));
+        assert!("first-last:0".parse::<Combine>().is_err());
+        assert!("product".parse::<Combine>().is_err());
+    }
+
+    #[test]
+    fn day1_other_languages() {
+        let german = DigitMatcher::new(&DigitVocabulary::language(Language::German));
+        assert_eq!(german.digits("achtzweineunfünf"), vec![8, 2, 9, 5]);
+        assert_eq!(german.digits("sechsiebenull3"), vec![6, 7, 0, 3]);
+
+        let french = DigitMatcher::new(&DigitVocabulary::language(Language::French));
+        assert_eq!(french.value_for_line("zérodeuxhuit"), Some(8));
+
+        let spanish = DigitMatcher::new(&DigitVocabulary::language(Language::Spanish));
+        assert_eq!(spanish.value_for_line("xcuatrocinconueve"), Some(49));
+    }
+
+    #[test]
+    fn day1_roman_numerals() {
+        let roman = DigitMatcher::new(&DigitVocabulary::language(Language::Roman));
+        assert_eq!(roman.digits("VIII"), vec![8]);
+        assert_eq!(roman.digits("xIVyIXz3"), vec![4, 9, 3]);
+        assert_eq!(roman.value_for_line("VII"), Some(77));
+    }
+
+    #[test]
+    fn day1_custom_vocabulary() {
+        let dutch = DigitVocabulary::parse("nul, een, twee, drie").unwrap();
+        assert_eq!(DigitMatcher::new(&dutch).digits("tweevier"), vec![2]);
+        assert_eq!(DigitMatcher::new(&dutch).value_for_line("xdrieentwee"), Some(32));
+
+        let skipped = DigitVocabulary::parse(",,two").unwrap();
+        assert_eq!(DigitMatcher::new(&skipped).digits("one two"), vec![2]);
+        assert!(DigitVocabulary::parse("a,b,c,d,e,f,g,h,i,j,k").is_err());
+
+        let day = Day1 { vocabulary: dutch, ..Day1::default() };
+        assert_eq!(day.part1(&day.parse("drie1\n2een").unwrap()).unwrap(), 33);
+        assert_eq!(day.part2(&day.parse("drie1\n2een").unwrap()).unwrap(), 52);
+    }
+
+    #[test]
+    fn day1_ambiguous_vocabulary_is_rejected() {
+        let invalid = |list| matches!(DigitVocabulary::parse(list), Err(Error::Validation(_)));
+        assert!(invalid("nul,een,een"));
+        assert!(invalid("nul,1,twee"));
+        assert!(invalid("nul,e2n"));
+        assert!(invalid("nul,een,eens"));
+        assert!(invalid("zeven,een,zes,ze"));
+        assert!(DigitVocabulary::parse("nul,een,,twee").is_ok());
     }
 }
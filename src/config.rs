@@ -0,0 +1,217 @@
+//! Defaults read from an `aoc.toml` file in the working directory or, failing
+//! that, the home directory:
+//!
+//! ```toml
+//! inputs = "inputs"
+//! format = "json"
+//!
+//! [day2]
+//! red = 12
+//! green = 13
+//! blue = 14
+//! ```
+//!
+//! Every setting is optional, and command-line flags take precedence. A
+//! relative `inputs` directory is relative to the config file.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use log::debug;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::registry;
+use crate::registry::Day;
+use crate::stone_game;
+
+/// The name of the config file.
+pub const FILE_NAME: &str = "aoc.toml";
+
+/// How results are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// The Day 2 bag contents. Missing colors keep the puzzle's values.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bag {
+    pub red: Option<i32>,
+    pub green: Option<i32>,
+    pub blue: Option<i32>,
+}
+
+/// The contents of a config file.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Directory of cached `day<N>.txt` inputs.
+    pub inputs: Option<PathBuf>,
+    pub format: Option<Format>,
+    #[serde(default)]
+    pub day2: Bag,
+}
+
+impl Config {
+    /// Parses the TOML contents of a config file.
+    pub fn parse(input: &str) -> Result<Config> {
+        toml::from_str(input).map_err(|err: toml::de::Error| {
+            let offset = err.span().map(|span| span.start).unwrap_or(0);
+            Error::parse_at(input, offset, err.message())
+        })
+    }
+
+    /// Reads the first `aoc.toml` found in `dirs`, or the defaults if there
+    /// is none. A relative `inputs` is resolved against the file's directory.
+    pub fn search(dirs: &[PathBuf]) -> Result<Config> {
+        for dir in dirs {
+            let path = dir.join(FILE_NAME);
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => {
+                    debug!("using config {}", path.display());
+                    let config = Config::parse(&contents).map_err(|err| err.in_file(&path))?;
+                    return Ok(Config {
+                        inputs: config.inputs.map(|inputs| dir.join(inputs)),
+                        ..config
+                    });
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(Error::io(path, err)),
+            }
+        }
+
+        Ok(Config::default())
+    }
+
+    /// Reads `aoc.toml` from the working directory or the home directory.
+    pub fn load() -> Result<Config> {
+        let mut dirs = vec![PathBuf::from(".")];
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(Path::new(&home).to_path_buf());
+        }
+        Config::search(&dirs)
+    }
+
+    /// The Day 2 solver for the configured bag, with `red`, `green` and
+    /// `blue` overriding it when given.
+    pub fn day2(
+        &self,
+        red: Option<i32>,
+        green: Option<i32>,
+        blue: Option<i32>,
+    ) -> stone_game::Day2 {
+        let default = stone_game::Day2::default();
+        stone_game::Day2 {
+            red: red.or(self.day2.red).unwrap_or(default.red),
+            green: green.or(self.day2.green).unwrap_or(default.green),
+            blue: blue.or(self.day2.blue).unwrap_or(default.blue),
+        }
+    }
+
+    /// Every registered day, with solvers set up from this config.
+    pub fn days(&self) -> Vec<Day> {
+        registry::days()
+            .into_iter()
+            .map(|day| match day.number {
+                2 => Day {
+                    number: 2,
+                    solver: Box::new(self.day2(None, None, None)),
+                },
+                _ => day,
+            })
+            .collect()
+    }
+
+    /// The registered day with the given number, set up from this config.
+    pub fn find(&self, number: u32) -> Option<Day> {
+        self.days().into_iter().find(|day| day.number == number)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::Answer;
+    use crate::solver::Part;
+
+    #[test]
+    fn parse_config() {
+        let config = Config::parse(
+            r#"
+            inputs = "puzzles"
+            format = "json"
+
+            [day2]
+            red = 20
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.inputs, Some(PathBuf::from("puzzles")));
+        assert_eq!(config.format, Some(Format::Json));
+        assert_eq!(config.day2.red, Some(20));
+        assert_eq!(config.day2.green, None);
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        assert!(matches!(Config::parse("input = \"x\""), Err(Error::Parse { .. })));
+        assert!(matches!(Config::parse("format = \"xml\""), Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn day2_overrides() {
+        let config = Config::parse("[day2]\nred = 20\ngreen = 1").unwrap();
+        let day2 = config.day2(None, Some(2), None);
+        assert_eq!((day2.red, day2.green, day2.blue), (20, 2, 14));
+
+        let game = "Game 1: 15 red, 1 green, 1 blue";
+        let day = config.find(2).unwrap();
+        assert_eq!(day.solver.solve(game, Part::One).unwrap(), Answer::Number(1));
+        let day = Config::default().find(2).unwrap();
+        assert_eq!(day.solver.solve(game, Part::One).unwrap(), Answer::Number(0));
+    }
+
+    #[test]
+    fn search_dirs_in_order() {
+        let root = std::env::temp_dir().join("aoc_2023_config");
+        let _ = std::fs::remove_dir_all(&root);
+        let (first, second) = (root.join("first"), root.join("second"));
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+
+        assert_eq!(Config::search(&[first.clone(), second.clone()]).unwrap(), Config::default());
+
+        std::fs::write(second.join(FILE_NAME), "format = \"json\"").unwrap();
+        let config = Config::search(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(config.format, Some(Format::Json));
+
+        std::fs::write(first.join(FILE_NAME), "format = \"text\"").unwrap();
+        let config = Config::search(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(config.format, Some(Format::Text));
+
+        let puzzles = second.join("puzzles");
+        std::fs::write(second.join(FILE_NAME), "inputs = \"puzzles\"").unwrap();
+        let config = Config::search(&[second]).unwrap();
+        assert_eq!(config.inputs, Some(puzzles));
+
+        let absolute = root.join("elsewhere");
+        std::fs::write(first.join(FILE_NAME), format!("inputs = {:?}", absolute)).unwrap();
+        let config = Config::search(std::slice::from_ref(&first)).unwrap();
+        assert_eq!(config.inputs, Some(absolute));
+
+        std::fs::write(first.join(FILE_NAME), "format = \"xml\"").unwrap();
+        match Config::search(std::slice::from_ref(&first)) {
+            Err(Error::File { path, .. }) => assert_eq!(path, first.join(FILE_NAME)),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
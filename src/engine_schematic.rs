@@ -1,26 +1,71 @@
+//! Day 3: Gear Ratios.
+//!
+//! The engine schematic is a grid of numbers, `.` padding and symbols. Part 1
+//! sums the numbers adjacent to a symbol, part 2 sums the ratios of `*`
+//! symbols touching exactly two part numbers.
+
+use log::debug;
+use log::warn;
 use nom::IResult;
 use nom::bytes::complete::is_a;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_while1;
 
+use crate::error::Error;
+use crate::error::Result;
+use crate::solver::Explanation;
+use crate::solver::Part;
+use crate::solver::Solver;
+
+/// A number on the schematic, positioned by its leftmost digit.
+#[derive(Debug)]
 pub struct PartNumber {
     pub number: i32,
     len: i32,
     pos: (i32,i32)
 }
 
+impl PartNumber {
+    /// The `(x, y)` position of the first digit.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    /// The number of digits.
+    pub fn width(&self) -> i32 {
+        self.len
+    }
+}
+
 struct Symbol {
     symbol: String,
     pos: (i32,i32)
 }
 
+/// A `*` symbol adjacent to exactly two part numbers.
+#[derive(Debug)]
 pub struct Gear {
     pos: (i32,i32),
     pub ratio: i32
 }
 
+impl Gear {
+    /// The `(x, y)` position of the `*` symbol.
+    pub fn pos(&self) -> (i32, i32) {
+        self.pos
+    }
+}
+
+/// A part number together with the symbol that made it one.
+#[derive(Debug)]
+pub struct ValidatedPartNumber<'a> {
+    pub part_number: &'a PartNumber,
+    pub symbol: &'a str,
+    pub symbol_pos: (i32, i32),
+}
+
 fn parse_part_number(input: &str) -> IResult<&str, &str> {
-    take_while1(|c: char| c.is_digit(10))(input)
+    take_while1(|c: char| c.is_ascii_digit())(input)
 }
 
 fn parse_ignored_char(input: &str) -> IResult<&str, &str> {
@@ -31,7 +76,7 @@ fn parse_symbol(input: &str) -> IResult<&str, &str> {
     is_a("!@#$%^&*()-=_+`~[]{}\\|;:'\",<>/?")(input)
 }
 
-fn parse_line(input: &str, y: i32) -> (Vec<PartNumber>, Vec<Symbol>) {
+fn parse_line(input: &str, y: i32) -> Result<(Vec<PartNumber>, Vec<Symbol>)> {
     let mut input = input;
     let mut part_numbers = Vec::new();
     let mut symbols = Vec::new();
@@ -39,8 +84,11 @@ fn parse_line(input: &str, y: i32) -> (Vec<PartNumber>, Vec<Symbol>) {
     
     while !input.is_empty() {
         if let Ok((updated_input, pn)) = parse_part_number(input) {
+            let number = pn.parse().map_err(|err| {
+                Error::parse(1, x as usize + 1, format!("invalid part number {:?}: {}", pn, err))
+            })?;
             part_numbers.push(PartNumber {
-                number: pn.parse().unwrap(),
+                number,
                 len: pn.len() as i32,
                 pos: (x,y)
             });
@@ -54,27 +102,46 @@ fn parse_line(input: &str, y: i32) -> (Vec<PartNumber>, Vec<Symbol>) {
             x += symbol.len() as i32;
             input = updated_input;
         } else {
-            println!("failed to parse: {:?}", input);
-            break;
+            let unexpected = input.chars().next().unwrap_or_default();
+            return Err(Error::parse(1, x as usize + 1, format!("unexpected character {:?}", unexpected)));
         }
     }
 
-    (part_numbers, symbols)
+    Ok((part_numbers, symbols))
 }
 
-fn parse_corpus(input: &str) -> (Vec<PartNumber>, Vec<Symbol>) {
+fn parse_corpus(input: &str) -> Result<(Vec<PartNumber>, Vec<Symbol>)> {
     let mut part_numbers = Vec::new();
     let mut symbols = Vec::new();
-    let mut y = 0;
 
-    for line in input.trim().lines() {
-        let (pns, ss) = parse_line(line.trim(), y);
+    let lines = input.lines()
+        .enumerate()
+        .skip_while(|(_, line)| line.trim().is_empty());
+
+    let mut width = None;
+
+    for (y, (line_index, line)) in lines.enumerate() {
+        let indent = line.len() - line.trim_start().len();
+        let row_width = line.trim().len();
+        match width {
+            None => width = Some(row_width),
+            Some(width) if width != row_width && row_width > 0 => warn!(
+                "line {}: row is {} wide but the first row is {}",
+                line_index + 1,
+                row_width,
+                width
+            ),
+            Some(_) => {}
+        }
+
+        let (pns, ss) = parse_line(line.trim(), y as i32)
+            .map_err(|err| err.at_line(line_index + 1, indent))?;
         part_numbers.extend(pns);
         symbols.extend(ss);
-        y += 1;
     }
 
-    (part_numbers, symbols)
+    debug!("parsed {} numbers and {} symbols", part_numbers.len(), symbols.len());
+    Ok((part_numbers, symbols))
 }
 
 fn adjacent(part_number: &PartNumber, symbol: &Symbol) -> bool {
@@ -99,7 +166,7 @@ fn adjacent(part_number: &PartNumber, symbol: &Symbol) -> bool {
     x_bound_match(symbol.pos.0) && y_bound_match(symbol.pos.1)
 }
 
-fn is_valid_part_number(part_number: &PartNumber, symbols: &Vec<Symbol>) -> bool {
+fn is_valid_part_number(part_number: &PartNumber, symbols: &[Symbol]) -> bool {
     for symbol in symbols {
         if adjacent(part_number, symbol) {
             return true;
@@ -109,8 +176,9 @@ fn is_valid_part_number(part_number: &PartNumber, symbols: &Vec<Symbol>) -> bool
     false
 }
 
-pub fn part_numbers_in_corpus(input: &str) -> Vec<PartNumber> {
-    let (part_numbers, symbols) = parse_corpus(input);
+/// Parses `input` and returns the part numbers adjacent to a symbol.
+pub fn part_numbers_in_corpus(input: &str) -> Result<Vec<PartNumber>> {
+    let (part_numbers, symbols) = parse_corpus(input)?;
     let mut valid_part_numbers = Vec::new();
 
     for part_number in part_numbers {
@@ -119,34 +187,144 @@ pub fn part_numbers_in_corpus(input: &str) -> Vec<PartNumber> {
         }
     }
 
-    valid_part_numbers
+    Ok(valid_part_numbers)
+}
+
+/// Parses `input` and returns its gears.
+pub fn gears_in_corpus(input: &str) -> Result<Vec<Gear>> {
+    Ok(Schematic::parse(input)?.gears())
 }
 
-pub fn gears_in_corpus(input: &str) -> Vec<Gear> {
-    let (part_numbers, symbols) = parse_corpus(input);
+/// A parsed engine schematic.
+pub struct Schematic {
+    part_numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+}
+
+impl Schematic {
+    /// Parses a schematic, ignoring blank lines before the first row.
+    pub fn parse(input: &str) -> Result<Schematic> {
+        let (part_numbers, symbols) = parse_corpus(input)?;
+        Ok(Schematic { part_numbers, symbols })
+    }
+
+    /// The numbers adjacent to at least one symbol.
+    pub fn part_numbers(&self) -> Vec<&PartNumber> {
+        self.part_numbers
+            .iter()
+            .filter(|pn| is_valid_part_number(pn, &self.symbols))
+            .collect()
+    }
 
-    let potential_gears: Vec<&Symbol> = symbols.iter()
-        .filter(|s| s.symbol == "*")
-        .collect();
+    /// The numbers adjacent to at least one symbol, each paired with the
+    /// first such symbol.
+    pub fn validated_part_numbers(&self) -> Vec<ValidatedPartNumber<'_>> {
+        let mut validated = Vec::new();
+
+        for part_number in &self.part_numbers {
+            if let Some(symbol) = self.symbols.iter().find(|s| adjacent(part_number, s)) {
+                validated.push(ValidatedPartNumber {
+                    part_number,
+                    symbol: &symbol.symbol,
+                    symbol_pos: symbol.pos,
+                });
+            }
+        }
 
-    let mut gears = Vec::new();
+        validated
+    }
 
-    for potential_gear in potential_gears {
-        let adjacent_part_numbers: Vec<&PartNumber> = part_numbers.iter()
-            .filter(|pn| adjacent(pn, potential_gear))
+    /// The `*` symbols adjacent to exactly two part numbers.
+    pub fn gears(&self) -> Vec<Gear> {
+        let potential_gears: Vec<&Symbol> = self.symbols.iter()
+            .filter(|s| s.symbol == "*")
             .collect();
 
-        if adjacent_part_numbers.len() == 2 {
-            let gear = Gear {
-                pos: potential_gear.pos,
-                ratio: adjacent_part_numbers[0].number * adjacent_part_numbers[1].number
-            };
+        let mut gears = Vec::new();
+
+        for potential_gear in potential_gears {
+            let adjacent_part_numbers: Vec<&PartNumber> = self.part_numbers.iter()
+                .filter(|pn| adjacent(pn, potential_gear))
+                .collect();
+
+            if adjacent_part_numbers.len() == 2 {
+                let gear = Gear {
+                    pos: potential_gear.pos,
+                    ratio: adjacent_part_numbers[0].number * adjacent_part_numbers[1].number
+                };
 
-            gears.push(gear);
+                gears.push(gear);
+            }
         }
+
+        gears
+    }
+
+    /// The gear whose `*` is at `pos`, if that symbol is a gear.
+    pub fn gear_at(&self, pos: (i32, i32)) -> Option<Gear> {
+        self.gears().into_iter().find(|gear| gear.pos == pos)
+    }
+
+    /// The part numbers adjacent to the cell at `pos`.
+    pub fn part_numbers_near(&self, pos: (i32, i32)) -> Vec<&PartNumber> {
+        let cell = Symbol { symbol: String::new(), pos };
+        self.part_numbers()
+            .into_iter()
+            .filter(|pn| adjacent(pn, &cell))
+            .collect()
+    }
+}
+
+/// Day 3 solver.
+pub struct Day3;
+
+impl Solver for Day3 {
+    type Parsed = Schematic;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, input: &str) -> Result<Schematic> {
+        Schematic::parse(input)
+    }
+
+    fn part1(&self, schematic: &Schematic) -> Result<i32> {
+        let mut pn_sum = 0;
+        for pn in schematic.part_numbers() {
+            pn_sum += pn.number;
+        }
+        Ok(pn_sum)
+    }
+
+    fn part2(&self, schematic: &Schematic) -> Result<i32> {
+        let mut gear_ratio_sum = 0;
+        for gear in schematic.gears() {
+            gear_ratio_sum += gear.ratio;
+        }
+        Ok(gear_ratio_sum)
     }
 
-    gears
+    fn explain(&self, schematic: &Schematic, part: Part) -> Result<Explanation> {
+        let pos = |(x, y): (i32, i32)| format!("{},{}", x, y);
+
+        if part == Part::Two {
+            let mut explanation = Explanation::new(&["gear at", "ratio"]);
+            for gear in schematic.gears() {
+                explanation.push_row(vec![pos(gear.pos()), gear.ratio.to_string()]);
+            }
+            return Ok(explanation);
+        }
+
+        let mut explanation = Explanation::new(&["number", "at", "symbol", "symbol at"]);
+        for validated in schematic.validated_part_numbers() {
+            explanation.push_row(vec![
+                validated.part_number.number.to_string(),
+                pos(validated.part_number.pos),
+                validated.symbol.to_string(),
+                pos(validated.symbol_pos),
+            ]);
+        }
+        Ok(explanation)
+    }
 }
 
 #[cfg(test)]
@@ -168,7 +346,7 @@ mod test {
         .664.598..
         ";
 
-        let (part_numbers, symbols) = parse_corpus(input);
+        let (part_numbers, symbols) = parse_corpus(input).unwrap();
 
         assert_eq!(part_numbers.len(), 10);
         assert_eq!(symbols.len(), 6);
@@ -207,7 +385,7 @@ mod test {
         .664.598..
         ";
 
-        let part_numbers = part_numbers_in_corpus(input);
+        let part_numbers = part_numbers_in_corpus(input).unwrap();
         assert_eq!(part_numbers.len(), 8);
 
         assert_eq!(part_numbers[0].number, 467);
@@ -250,10 +428,89 @@ mod test {
         .664.598..
         ";
 
-        let gears = gears_in_corpus(input);
+        let gears = gears_in_corpus(input).unwrap();
         assert_eq!(gears.len(), 2);
 
         assert_eq!(gears[0].ratio, 467 * 35);
         assert_eq!(gears[1].ratio, 755 * 598);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_queries() {
+        let input = "
+        467..114..
+        ...*......
+        ..35..633.
+        ......#...
+        617*......
+        ";
+
+        let schematic = Schematic::parse(input).unwrap();
+        assert_eq!(schematic.gear_at((3, 1)).unwrap().ratio, 467 * 35);
+        assert!(schematic.gear_at((3, 4)).is_none());
+
+        let near: Vec<i32> = schematic
+            .part_numbers_near((6, 3))
+            .iter()
+            .map(|pn| pn.number)
+            .collect();
+        assert_eq!(near, vec![633]);
+        assert!(schematic.part_numbers_near((9, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_validated_part_numbers() {
+        let input = "
+        467..114..
+        ...*......
+        ..35..633.
+        ......#...
+        ";
+
+        let schematic = Schematic::parse(input).unwrap();
+        let validated: Vec<(i32, &str, (i32, i32))> = schematic
+            .validated_part_numbers()
+            .iter()
+            .map(|v| (v.part_number.number, v.symbol, v.symbol_pos))
+            .collect();
+
+        assert_eq!(
+            validated,
+            vec![(467, "*", (3, 1)), (35, "*", (3, 1)), (633, "#", (6, 3))]
+        );
+    }
+
+    #[test]
+    fn day3_solver_example() {
+        let input = "
+        467..114..
+        ...*......
+        ..35..633.
+        ......#...
+        617*......
+        .....+.58.
+        ..592.....
+        ......755.
+        ...$.*....
+        .664.598..
+        ";
+
+        let schematic = Day3.parse(input).unwrap();
+        assert_eq!(Day3.part1(&schematic).unwrap(), 4361);
+        assert_eq!(Day3.part2(&schematic).unwrap(), 467835);
+    }
+
+    #[test]
+    fn test_unexpected_character() {
+        let input = "
+        467..114..
+        ...*..a...
+        ";
+
+        match parse_corpus(input) {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (3, 15)),
+            Err(other) => panic!("unexpected error {:?}", other),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+}
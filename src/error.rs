@@ -0,0 +1,233 @@
+//! The error type shared by every module.
+
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::parallel;
+
+/// Everything that can go wrong reading, parsing or validating input, or
+/// talking to the puzzle site.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading the file at `path` failed.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The input doesn't match the expected format. Lines and columns count
+    /// from 1.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The input parsed but can't be solved as given.
+    Validation(String),
+    /// A request to `url` failed or got a response that made no sense.
+    Network { url: String, message: String },
+    /// An answer wasn't submitted because earlier verdicts rule it out.
+    Refused(String),
+    /// A file of the tool's own, like `aoc.toml` or an answers file, at
+    /// `path` is malformed.
+    File { path: PathBuf, source: Box<Error> },
+}
+
+impl Error {
+    pub fn io(path: impl AsRef<Path>, source: std::io::Error) -> Error {
+        Error::Io {
+            path: path.as_ref().to_path_buf(),
+            source,
+        }
+    }
+
+    /// A failed request to `url`. HTTP client errors often start with the
+    /// URL already, so it's stripped from `message` to print it only once.
+    pub fn network(url: impl Into<String>, message: impl fmt::Display) -> Error {
+        let url = url.into();
+        let message = message.to_string();
+        let message = match message.strip_prefix(&format!("{}: ", url)) {
+            Some(rest) => rest.to_string(),
+            None => message,
+        };
+        Error::Network { url, message }
+    }
+
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Error {
+        Error::Parse {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Builds a parse error from a byte offset into a (possibly multi-line)
+    /// input, counting lines and columns from 1.
+    pub fn parse_at(input: &str, offset: usize, message: impl Into<String>) -> Error {
+        let before = &input[..offset.min(input.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+
+        Error::parse(line, column, message)
+    }
+
+    /// Marks an error as coming from the tool's own file at `path`, rather
+    /// than from a puzzle input.
+    pub fn in_file(self, path: impl AsRef<Path>) -> Error {
+        Error::File {
+            path: path.as_ref().to_path_buf(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Moves a parse error produced for a single line to its position in the
+    /// surrounding corpus.
+    pub fn at_line(self, line: usize, indent: usize) -> Error {
+        match self {
+            Error::Parse {
+                column, message, ..
+            } => Error::Parse {
+                line,
+                column: column + indent,
+                message,
+            },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "parse error at line {}, column {}: {}", line, column, message),
+            Error::Validation(message) => write!(f, "invalid input: {}", message),
+            Error::Network { url, message } => write!(f, "{}: {}", url, message),
+            Error::Refused(message) => write!(f, "refused: {}", message),
+            Error::File { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::File { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A `Result` using this crate's [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Splits a corpus into its non-blank, trimmed lines, keeping the 1-based
+/// line number and the width of the indentation stripped from each one.
+pub fn numbered_lines(corpus: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    corpus.lines().enumerate().filter_map(|(i, line)| {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            let indent = line.len() - line.trim_start().len();
+            Some((i + 1, indent, trimmed))
+        }
+    })
+}
+
+/// Applies `f` to each of the [`numbered_lines`] of `corpus`, concurrently
+/// with the `parallel` feature. Returns the results in line order, or the
+/// error from the first line that failed.
+pub fn try_map_lines<T, F>(corpus: &str, f: F) -> Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(usize, usize, &str) -> Result<T> + Sync + Send,
+{
+    let lines: Vec<(usize, usize, &str)> = numbered_lines(corpus).collect();
+    parallel::map(&lines, |&(line_number, indent, line)| f(line_number, indent, line))
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_at_offset() {
+        let input = "abc\ndef\nghi";
+
+        match Error::parse_at(input, 5, "bad") {
+            Error::Parse { line, column, .. } => assert_eq!((line, column), (2, 2)),
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        match Error::parse_at(input, 0, "bad") {
+            Error::Parse { line, column, .. } => assert_eq!((line, column), (1, 1)),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_error_at_line() {
+        match Error::parse(1, 3, "bad").at_line(7, 4) {
+            Error::Parse { line, column, .. } => assert_eq!((line, column), (7, 7)),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn numbered_lines_skip_blanks() {
+        let lines: Vec<(usize, usize, &str)> = numbered_lines("\n  a\n\nb \n").collect();
+        assert_eq!(lines, vec![(2, 2, "a"), (4, 0, "b")]);
+    }
+
+    #[test]
+    fn try_map_lines_reports_first_error() {
+        let parse = |line_number: usize, _: usize, line: &str| {
+            line.parse::<i32>()
+                .map_err(|_| Error::parse(line_number, 1, "not a number"))
+        };
+
+        assert_eq!(try_map_lines("1\n\n2\n3", parse).unwrap(), vec![1, 2, 3]);
+        match try_map_lines("1\nx\n2\ny", parse) {
+            Err(Error::Parse { line, .. }) => assert_eq!(line, 2),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn display_errors() {
+        assert_eq!(
+            Error::parse(2, 5, "expected digit").to_string(),
+            "parse error at line 2, column 5: expected digit"
+        );
+        assert_eq!(
+            Error::Validation("no cards".to_string()).to_string(),
+            "invalid input: no cards"
+        );
+        assert_eq!(
+            Error::parse(1, 10, "unknown variant").in_file("aoc.toml").to_string(),
+            "aoc.toml: parse error at line 1, column 10: unknown variant"
+        );
+    }
+
+    #[test]
+    fn network_errors_name_the_url_once() {
+        let url = "https://example.com/2023/day/1/input";
+        assert_eq!(
+            Error::network(url, format!("{}: Connection Failed", url)).to_string(),
+            "https://example.com/2023/day/1/input: Connection Failed"
+        );
+        assert_eq!(
+            Error::network(url, "unrecognized response").to_string(),
+            "https://example.com/2023/day/1/input: unrecognized response"
+        );
+    }
+}
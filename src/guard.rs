@@ -0,0 +1,190 @@
+//! Checks an answer against what earlier submissions revealed, before it's
+//! sent anywhere.
+//!
+//! A "too high" verdict rules out that answer and everything above it, and
+//! "too low" everything below. Wrong answers without a hint rule out just
+//! themselves.
+
+use std::fmt;
+
+use crate::history::History;
+use crate::history::Verdict;
+use crate::solver::Answer;
+use crate::solver::Part;
+
+/// The range numeric answers must fall in, from earlier "too high" and "too
+/// low" verdicts. Both ends are exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bounds {
+    /// The highest answer judged too low.
+    pub above: Option<i64>,
+    /// The lowest answer judged too high.
+    pub below: Option<i64>,
+}
+
+impl Bounds {
+    /// Whether `number` is inside the bounds.
+    pub fn contains(&self, number: i64) -> bool {
+        self.above.is_none_or(|above| number > above)
+            && self.below.is_none_or(|below| number < below)
+    }
+}
+
+/// Why an answer shouldn't be submitted.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Refusal {
+    /// The part was already solved with a different answer.
+    AlreadySolved { answer: Answer },
+    /// This exact answer was already judged wrong.
+    AlreadyWrong { verdict: Verdict },
+    /// An answer at or below this one was judged too high.
+    AtOrAbove { limit: i64 },
+    /// An answer at or above this one was judged too low.
+    AtOrBelow { limit: i64 },
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::AlreadySolved { answer } => write!(f, "already solved with {}", answer),
+            Refusal::AlreadyWrong { verdict } => write!(f, "already submitted: {}", verdict),
+            Refusal::AtOrAbove { limit } => write!(f, "{} was already too high", limit),
+            Refusal::AtOrBelow { limit } => write!(f, "{} was already too low", limit),
+        }
+    }
+}
+
+/// The bounds earlier verdicts put on a day and part.
+pub fn bounds(history: &History, day: u32, part: Part) -> Bounds {
+    let mut bounds = Bounds::default();
+
+    for submission in history.submissions(day, part) {
+        let Answer::Number(number) = submission.answer else {
+            continue;
+        };
+
+        match submission.verdict {
+            Verdict::TooHigh => {
+                bounds.below = Some(bounds.below.map_or(number, |below| below.min(number)));
+            }
+            Verdict::TooLow => {
+                bounds.above = Some(bounds.above.map_or(number, |above| above.max(number)));
+            }
+            _ => {}
+        }
+    }
+
+    bounds
+}
+
+/// Refuses `answer` if the history already shows it can't be right. An
+/// answer already confirmed correct passes.
+pub fn check(history: &History, day: u32, part: Part, answer: &Answer) -> Result<(), Refusal> {
+    if let Some(correct) = history.correct(day, part) {
+        if correct == answer {
+            return Ok(());
+        }
+        return Err(Refusal::AlreadySolved {
+            answer: correct.clone(),
+        });
+    }
+
+    if let Some(verdict) = history.verdict_for(day, part, answer) {
+        if verdict.is_wrong() {
+            return Err(Refusal::AlreadyWrong { verdict });
+        }
+    }
+
+    if let Answer::Number(number) = answer {
+        let bounds = bounds(history, day, part);
+        if let Some(below) = bounds.below.filter(|&below| *number >= below) {
+            return Err(Refusal::AtOrAbove { limit: below });
+        }
+        if let Some(above) = bounds.above.filter(|&above| *number <= above) {
+            return Err(Refusal::AtOrBelow { limit: above });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::Submission;
+
+    fn history(submissions: &[(i64, Verdict)]) -> History {
+        let mut history = History::default();
+        for &(answer, verdict) in submissions {
+            history.record(Submission {
+                day: 1,
+                part: Part::Two,
+                answer: Answer::Number(answer),
+                verdict,
+            });
+        }
+        history
+    }
+
+    fn check_number(history: &History, answer: i64) -> Result<(), Refusal> {
+        check(history, 1, Part::Two, &Answer::Number(answer))
+    }
+
+    #[test]
+    fn bounds_from_verdicts() {
+        let history = history(&[
+            (100, Verdict::TooHigh),
+            (10, Verdict::TooLow),
+            (80, Verdict::TooHigh),
+            (20, Verdict::TooLow),
+            (50, Verdict::Incorrect),
+        ]);
+
+        let bounds = bounds(&history, 1, Part::Two);
+        assert_eq!(bounds, Bounds { above: Some(20), below: Some(80) });
+        assert!(bounds.contains(21));
+        assert!(!bounds.contains(80));
+        assert_eq!(super::bounds(&history, 1, Part::One), Bounds::default());
+    }
+
+    #[test]
+    fn refuses_out_of_bounds() {
+        let history = history(&[(100, Verdict::TooHigh), (10, Verdict::TooLow)]);
+
+        assert_eq!(
+            check_number(&history, 100),
+            Err(Refusal::AlreadyWrong { verdict: Verdict::TooHigh })
+        );
+        assert_eq!(check_number(&history, 150), Err(Refusal::AtOrAbove { limit: 100 }));
+        assert_eq!(check_number(&history, 5), Err(Refusal::AtOrBelow { limit: 10 }));
+        assert_eq!(check_number(&history, 50), Ok(()));
+    }
+
+    #[test]
+    fn refuses_previous_wrong_answers() {
+        let history = history(&[(42, Verdict::Incorrect), (43, Verdict::Wait)]);
+
+        assert_eq!(
+            check_number(&history, 42),
+            Err(Refusal::AlreadyWrong { verdict: Verdict::Incorrect })
+        );
+        assert_eq!(check_number(&history, 43), Ok(()));
+    }
+
+    #[test]
+    fn solved_parts() {
+        let history = history(&[(42, Verdict::Correct)]);
+
+        assert_eq!(check_number(&history, 42), Ok(()));
+        assert_eq!(
+            check_number(&history, 41),
+            Err(Refusal::AlreadySolved { answer: Answer::Number(42) })
+        );
+    }
+
+    #[test]
+    fn text_answers_ignore_bounds() {
+        let history = history(&[(100, Verdict::TooHigh)]);
+        assert_eq!(check(&history, 1, Part::Two, &Answer::Text("ABC".to_string())), Ok(()));
+    }
+}
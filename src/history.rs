@@ -0,0 +1,187 @@
+//! A local record of submitted answers and the verdicts they got, kept as
+//! TOML:
+//!
+//! ```toml
+//! [[submission]]
+//! day = 4
+//! part = 2
+//! answer = 31
+//! verdict = "too_high"
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::solver::Answer;
+use crate::solver::Part;
+
+/// What the puzzle site said about a submitted answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint about which way.
+    Incorrect,
+    /// Submitted too soon after a previous answer; not judged.
+    Wait,
+}
+
+impl Verdict {
+    /// Whether the answer was judged wrong.
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect)
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => f.pad("correct"),
+            Verdict::TooHigh => f.pad("too high"),
+            Verdict::TooLow => f.pad("too low"),
+            Verdict::Incorrect => f.pad("incorrect"),
+            Verdict::Wait => f.pad("wait"),
+        }
+    }
+}
+
+/// One submitted answer.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Submission {
+    pub day: u32,
+    pub part: Part,
+    pub answer: Answer,
+    pub verdict: Verdict,
+}
+
+/// Every answer submitted so far, oldest first.
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct History {
+    #[serde(default, rename = "submission")]
+    submissions: Vec<Submission>,
+}
+
+impl History {
+    /// Parses the TOML contents of a history file.
+    pub fn parse(input: &str) -> Result<History> {
+        toml::from_str(input).map_err(|err: toml::de::Error| {
+            let offset = err.span().map(|span| span.start).unwrap_or(0);
+            Error::parse_at(input, offset, err.message())
+        })
+    }
+
+    /// Reads a history file, or starts an empty history if it doesn't exist.
+    pub fn load(path: &Path) -> Result<History> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => History::parse(&contents).map_err(|err| err.in_file(path)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(History::default()),
+            Err(err) => Err(Error::io(path, err)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string(self).expect("history should serialize to TOML");
+        std::fs::write(path, contents).map_err(|err| Error::io(path, err))
+    }
+
+    pub fn record(&mut self, submission: Submission) {
+        self.submissions.push(submission);
+    }
+
+    /// The submissions for a day and part, oldest first.
+    pub fn submissions(&self, day: u32, part: Part) -> impl Iterator<Item = &Submission> {
+        self.submissions
+            .iter()
+            .filter(move |submission| submission.day == day && submission.part == part)
+    }
+
+    /// The answer confirmed correct for a day and part, if any.
+    pub fn correct(&self, day: u32, part: Part) -> Option<&Answer> {
+        self.submissions(day, part)
+            .find(|submission| submission.verdict == Verdict::Correct)
+            .map(|submission| &submission.answer)
+    }
+
+    /// The verdict `answer` got when it was last judged, if it ever was.
+    pub fn verdict_for(&self, day: u32, part: Part, answer: &Answer) -> Option<Verdict> {
+        self.submissions(day, part)
+            .filter(|submission| &submission.answer == answer)
+            .filter(|submission| submission.verdict != Verdict::Wait)
+            .last()
+            .map(|submission| submission.verdict)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn submission(part: Part, answer: i64, verdict: Verdict) -> Submission {
+        Submission {
+            day: 4,
+            part,
+            answer: Answer::Number(answer),
+            verdict,
+        }
+    }
+
+    #[test]
+    fn parse_history() {
+        let history = History::parse(
+            r#"
+            [[submission]]
+            day = 4
+            part = 2
+            answer = 31
+            verdict = "too_high"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            history.submissions(4, Part::Two).collect::<Vec<_>>(),
+            vec![&submission(Part::Two, 31, Verdict::TooHigh)]
+        );
+        assert_eq!(History::parse("").unwrap(), History::default());
+        assert!(History::parse("[[submission]]\nday = 4\npart = 3").is_err());
+    }
+
+    #[test]
+    fn lookups() {
+        let mut history = History::default();
+        history.record(submission(Part::Two, 31, Verdict::Wait));
+        history.record(submission(Part::Two, 31, Verdict::TooHigh));
+        history.record(submission(Part::Two, 30, Verdict::Correct));
+        history.record(submission(Part::One, 12, Verdict::Wait));
+
+        assert_eq!(history.correct(4, Part::Two), Some(&Answer::Number(30)));
+        assert_eq!(history.correct(4, Part::One), None);
+        assert_eq!(history.verdict_for(4, Part::Two, &Answer::Number(31)), Some(Verdict::TooHigh));
+        assert_eq!(history.verdict_for(4, Part::One, &Answer::Number(12)), None);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("aoc_2023_history_test.toml");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(History::load(&path).unwrap(), History::default());
+
+        let mut history = History::default();
+        history.record(submission(Part::One, 13, Verdict::Correct));
+        history.save(&path).unwrap();
+        assert_eq!(History::load(&path).unwrap(), history);
+
+        std::fs::write(&path, "[[submission]]\nday = \"x\"").unwrap();
+        let err = History::load(&path).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("{}:", path.display())), "{}", err);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
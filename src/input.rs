@@ -0,0 +1,75 @@
+//! Loading puzzle input from a file or stdin.
+
+use std::io::Read;
+
+use log::info;
+
+use crate::error::Error;
+use crate::error::Result;
+
+/// The path that selects stdin.
+pub const STDIN: &str = "-";
+
+/// Whether `path` selects stdin.
+pub fn is_stdin(path: Option<&str>) -> bool {
+    matches!(path, None | Some(STDIN))
+}
+
+/// Reads `path`, or stdin when no path (or `-`) was given.
+pub fn read_input(path: Option<&str>) -> Result<String> {
+    read_input_from(path, std::io::stdin())
+}
+
+/// Like [`read_input`], reading from `stdin` instead of the process's stdin.
+pub fn read_input_from(path: Option<&str>, mut stdin: impl Read) -> Result<String> {
+    match path {
+        Some(path) if !is_stdin(Some(path)) => {
+            info!("reading input from {}", path);
+            std::fs::read_to_string(path).map_err(|err| Error::io(path, err))
+        }
+        _ => {
+            info!("reading input from stdin");
+            let mut input = String::new();
+            stdin
+                .read_to_string(&mut input)
+                .map_err(|err| Error::io("<stdin>", err))?;
+            Ok(input)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_stdin_without_path() {
+        let stdin = "Card 1: 1 | 1\n".as_bytes();
+        assert_eq!(read_input_from(None, stdin).unwrap(), "Card 1: 1 | 1\n");
+    }
+
+    #[test]
+    fn reads_stdin_for_dash() {
+        let stdin = "1abc2\n".as_bytes();
+        assert_eq!(read_input_from(Some("-"), stdin).unwrap(), "1abc2\n");
+    }
+
+    #[test]
+    fn reads_file_path() {
+        let path = std::env::temp_dir().join("aoc_2023_input_test.txt");
+        std::fs::write(&path, "Game 1: 3 blue").unwrap();
+
+        let stdin = "unused".as_bytes();
+        let input = read_input_from(path.to_str(), stdin).unwrap();
+        assert_eq!(input, "Game 1: 3 blue");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_file() {
+        let stdin = "unused".as_bytes();
+        let result = read_input_from(Some("inputs/does-not-exist.txt"), stdin);
+        assert!(matches!(result, Err(Error::Io { .. })));
+    }
+}
@@ -0,0 +1,43 @@
+//! Advent of Code 2023 puzzle solutions.
+//!
+//! Each day lives in its own module with a parser for the puzzle input and a
+//! [`solver::Solver`] implementation. The [`registry`] ties the days together
+//! so they can be run generically:
+//!
+//! ```
+//! use aoc_2023::registry;
+//! use aoc_2023::solver::Answer;
+//! use aoc_2023::solver::Part;
+//!
+//! let day = registry::find(4).unwrap();
+//! let answer = day.solver.solve("Card 1: 41 48 83 | 83 41 6", Part::One).unwrap();
+//! assert_eq!(answer, Answer::Number(2));
+//! ```
+//!
+//! The `parallel` feature spreads independent lines and days over a thread
+//! pool (see [`parallel`]); answers are the same either way.
+//!
+//! Parse warnings and debug traces go through the [`log`] facade. Nothing is
+//! printed unless the application installs a logger, such as `env_logger`.
+
+pub mod answers;
+pub mod bench;
+pub mod calibration;
+pub mod config;
+pub mod engine_schematic;
+pub mod error;
+pub mod guard;
+pub mod history;
+pub mod input;
+pub mod parallel;
+pub mod registry;
+pub mod repl;
+pub mod runner;
+pub mod scaffold;
+pub mod scratch_cards;
+pub mod solver;
+pub mod stone_game;
+pub mod stone_game_nom;
+pub mod store;
+pub mod submit;
+pub mod watch;
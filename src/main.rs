@@ -1,136 +1,1239 @@
-#![allow(dead_code)]
-
-mod calibration;
-mod engine_schematic;
-mod scratch_cards;
-mod stone_game;
-mod stone_game_nom;
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
 
+use clap::builder::PossibleValuesParser;
+use clap::builder::TypedValueParser;
+use clap::CommandFactory;
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
+use clap_complete::Shell;
+use log::LevelFilter;
+use log::Log;
+use log::Metadata;
+use log::Record;
+use serde::Serialize;
+
+use aoc_2023::answers;
+use aoc_2023::answers::Answers;
+use aoc_2023::answers::Status;
+use aoc_2023::answers::Verification;
+use aoc_2023::bench;
+use aoc_2023::bench::BenchReport;
+use aoc_2023::calibration;
+use aoc_2023::calibration::Combine;
+use aoc_2023::calibration::DigitMatcher;
+use aoc_2023::calibration::DigitVocabulary;
+use aoc_2023::calibration::Language;
+use aoc_2023::calibration::LineReport;
+use aoc_2023::config::Config;
+use aoc_2023::config::Format;
+use aoc_2023::error::Error;
+use aoc_2023::error::Result;
+use aoc_2023::history::History;
+use aoc_2023::history::Verdict;
+use aoc_2023::input;
+use aoc_2023::registry;
+use aoc_2023::registry::Day;
+use aoc_2023::repl;
+use aoc_2023::runner;
+use aoc_2023::runner::DayOutcome;
+use aoc_2023::runner::Outcome;
+use aoc_2023::runner::RunResult;
+use aoc_2023::scaffold;
+use aoc_2023::solver::Answer;
+use aoc_2023::solver::Explanation;
+use aoc_2023::solver::Part;
+use aoc_2023::store;
+use aoc_2023::store::InputStore;
+use aoc_2023::submit;
+use aoc_2023::watch;
+use aoc_2023::watch::FileWatcher;
+
+// `--day` only accepts registered days, which also lists them in the shell
+// completions and man page.
+fn registered_day() -> impl TypedValueParser<Value = u32> {
+    let days: Vec<String> = registry::days()
+        .iter()
+        .map(|day| day.number.to_string())
+        .collect();
+    PossibleValuesParser::new(days).map(|day| day.parse::<u32>().expect("day numbers should parse"))
+}
+
+// Exit codes other than success. Clap exits with 2 on usage errors.
+const EXIT_FAILED: u8 = 1;
+const EXIT_INVALID_INPUT: u8 = 3;
+const EXIT_IO: u8 = 4;
+const EXIT_NETWORK: u8 = 5;
+const EXIT_REFUSED: u8 = 6;
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  an answer failed verification or a submission was wrong
+  2  invalid command-line arguments
+  3  an input didn't parse or can't be solved as given
+  4  reading or writing a file failed, or aoc.toml, answers or history is malformed
+  5  the puzzle site couldn't be reached or sent an unexpected response
+  6  an answer wasn't submitted because earlier verdicts rule it out";
+
+fn exit_code(err: &Error) -> u8 {
+    match err {
+        Error::Io { .. } | Error::File { .. } => EXIT_IO,
+        Error::Network { .. } => EXIT_NETWORK,
+        Error::Refused(_) => EXIT_REFUSED,
+        Error::Parse { .. } | Error::Validation(_) => EXIT_INVALID_INPUT,
+    }
+}
 
+/// Advent of Code 2023 solutions.
 #[derive(Parser)]
+#[command(name = "aoc_2023", version, after_help = EXIT_CODES)]
 struct Args {
+    /// Output format. Defaults to the config file's, then text.
+    #[arg(long, global = true, value_enum)]
+    format: Option<Format>,
+
+    /// Directory of cached `day<N>.txt` inputs, overriding AOC_INPUT_ROOT
+    /// and the config file.
+    #[arg(long, global = true)]
+    inputs: Option<PathBuf>,
+
+    /// Print more diagnostics to stderr: -v for progress, -vv for debug
+    /// traces, -vvv for everything.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print errors to stderr.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Command,
 }
 
+impl Args {
+    /// The most detailed log level to print. Warnings show by default.
+    fn log_level(&self) -> LevelFilter {
+        if self.quiet {
+            return LevelFilter::Error;
+        }
+        match self.verbose {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+
+    /// Rejects combinations of arguments clap can't express on its own, as
+    /// usage errors.
+    fn check_usage(&self) -> std::result::Result<(), clap::Error> {
+        let conflict = |message: &str| {
+            Err(Args::command().error(clap::error::ErrorKind::ArgumentConflict, message))
+        };
+
+        match &self.command {
+            Command::Run {
+                day, vocabulary, ..
+            } if *day != 1 && vocabulary.is_set() => {
+                conflict("--language and --words only apply to day 1")
+            }
+            Command::Run {
+                watch: true,
+                input_path: Some(path),
+                ..
+            } if path == input::STDIN => conflict("--watch needs an input file, not stdin"),
+            // Commands are read from stdin, so the input has to come from a file.
+            Command::Repl {
+                input_path: Some(path),
+                ..
+            } if path == input::STDIN => {
+                conflict("repl reads commands from stdin; pass an input file")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Writes log records to stderr, prefixed with their level.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}: {}", record.level().as_str().to_lowercase(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// The words day 1 reads as digits.
+#[derive(clap::Args)]
+struct VocabularyArgs {
+    /// The language digits may be spelled out in, for day 1. Defaults to
+    /// English.
+    #[arg(long, value_enum)]
+    language: Option<Language>,
+
+    /// Comma-separated words for 0, 1, 2 and so on, instead of a
+    /// language. Leave a word empty to skip its digit.
+    #[arg(long, conflicts_with = "language")]
+    words: Option<String>,
+}
+
+impl VocabularyArgs {
+    fn is_set(&self) -> bool {
+        self.language.is_some() || self.words.is_some()
+    }
+
+    fn vocabulary(&self) -> Result<DigitVocabulary> {
+        match &self.words {
+            Some(words) => DigitVocabulary::parse(words),
+            None => Ok(DigitVocabulary::language(self.language.unwrap_or(Language::English))),
+        }
+    }
+}
+
+/// How `day1 --report` shows what was matched on each line.
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportStyle {
+    /// A table of tokens and their byte spans.
+    Table,
+    /// Each line with its tokens colored, or bracketed when stdout isn't a
+    /// terminal or `NO_COLOR` is set.
+    Highlight,
+}
+
 #[derive(Subcommand)]
 enum Command {
+    /// Solve one part of a registered day.
+    Run {
+        #[arg(long, value_parser = registered_day())]
+        day: u32,
+
+        #[arg(long, required_unless_present = "check")]
+        part: Option<Part>,
+
+        /// Show how each item contributes to the answer.
+        #[arg(long, conflicts_with = "watch")]
+        explain: bool,
+
+        /// Only parse the input to check its format; don't solve.
+        #[arg(long, conflicts_with_all = ["explain", "watch"])]
+        check: bool,
+
+        /// Re-run whenever the input file changes.
+        #[arg(long)]
+        watch: bool,
+
+        /// How often to check the input file for changes, in milliseconds.
+        #[arg(long, default_value_t = 500, requires = "watch")]
+        interval: u64,
+
+        #[command(flatten)]
+        vocabulary: VocabularyArgs,
+
+        /// Input file, or `-` for stdin. Defaults to the cached input for the day.
+        input_path: Option<String>,
+    },
+    /// Solve every registered day from the cached inputs.
+    RunAll {
+        /// Only parse each input to check its format; don't solve.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Time parsing and solving a day.
+    Bench {
+        #[arg(long, value_parser = registered_day())]
+        day: u32,
+
+        #[arg(
+            long,
+            default_value_t = 100,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        runs: usize,
+
+        /// Input file, or `-` for stdin. Defaults to the cached input for the day.
+        input_path: Option<String>,
+    },
+    /// Query a day's parsed input interactively.
+    Repl {
+        #[arg(long, value_parser = registered_day())]
+        day: u32,
+
+        /// Input file. Defaults to the cached input for the day.
+        input_path: Option<String>,
+    },
+    /// Check every answer against the answers file.
+    Verify {
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
+    /// Solve one part of a day and submit the answer.
+    Submit {
+        #[arg(long, value_parser = registered_day())]
+        day: u32,
+
+        #[arg(long)]
+        part: Part,
+
+        /// Where submitted answers and their verdicts are recorded.
+        #[arg(long, default_value = "submissions.toml")]
+        history: PathBuf,
+
+        /// Answers confirmed correct are recorded here.
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+
+        /// Input file, or `-` for stdin. Defaults to the cached input for the day.
+        input_path: Option<String>,
+    },
+    /// Scaffold and register a solver module for a new day.
+    NewDay {
+        #[arg(value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+
+        /// Root of the crate to add the day to.
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
+    /// Day 1: sum of calibration values.
     Day1 {
-        input_path: String,
+        /// 1 counts only numerals; 2 also counts digits spelled out as words.
+        #[arg(long, default_value = "2")]
+        part: Part,
+
+        #[command(flatten)]
+        vocabulary: VocabularyArgs,
+
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        /// How each line's digits make its value: first-last, first-last:<n>
+        /// (the first and last n digits), all or sum.
+        #[arg(long, default_value = "first-last")]
+        combine: Combine,
+
+        /// Instead of solving, show every token matched on each line.
+        #[arg(long, value_enum, conflicts_with = "explain")]
+        report: Option<ReportStyle>,
+
+        input_path: Option<String>,
     },
+    /// Day 2: sum of the ids of possible games. The bag defaults to the
+    /// config file's, then 12 red, 13 green and 14 blue.
     Day2 {
         #[arg(short)]
-        red: i32,
+        red: Option<i32>,
 
         #[arg(short)]
-        green: i32,
+        green: Option<i32>,
 
         #[arg(short)]
-        blue: i32,
-        input_path: String,
+        blue: Option<i32>,
+
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        input_path: Option<String>,
     },
+    /// Day 2 part 2: sum of game powers.
     Day2Part2 {
-        input_path: String,
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        input_path: Option<String>,
     },
+    /// Day 3: sum of part numbers.
     Day3 {
-        input_path: String,
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        input_path: Option<String>,
     },
+    /// Day 3 part 2: sum of gear ratios.
     Day3Part2 {
-        input_path: String,
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        input_path: Option<String>,
     },
+    /// Day 4: sum of card scores.
     Day4 {
-        input_path: String,
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        input_path: Option<String>,
     },
+    /// Day 4 part 2: number of cards held.
     Day4Part2 {
-        input_path: String,
+        /// Show how each item contributes to the answer.
+        #[arg(long)]
+        explain: bool,
+
+        input_path: Option<String>,
+    },
+    /// Print shell completions.
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
     },
+    /// Print a man page.
+    Man,
 }
 
-fn main() {
-    let args = Args::parse();
+fn print_json(value: &impl Serialize) {
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("results should serialize to JSON")
+    );
+}
 
-    match args.command {
-        Command::Day1 { input_path } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let calibration = calibration::value_for_corpus(&input);
+fn report(format: Format, result: &RunResult, label: &str) {
+    match format {
+        Format::Text => println!("{}: {}", label, result.answer),
+        Format::Json => print_json(result),
+    }
+}
 
-            println!("Calibration: {}", calibration);
-        },
+#[derive(Serialize)]
+struct Explained<'a> {
+    #[serde(flatten)]
+    result: &'a RunResult,
+    explanation: &'a Explanation,
+}
 
-        Command::Day2 {
-            red,
-            green,
-            blue,
-            input_path,
-        } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let games = stone_game::StoneGame::parse_lines(&input);
-            let mut id_sum = 0;
+// The files `new-day` wrote, as printed with `--format json`.
+#[derive(Serialize)]
+struct Scaffolded {
+    day: u32,
+    written: Vec<PathBuf>,
+}
+
+// Solves `part` and reports it, preceded by the day's breakdown when
+// `explain` is set.
+fn report_part(
+    format: Format,
+    day: &Day,
+    input: &str,
+    part: Part,
+    label: &str,
+    explain: bool,
+) -> Result<()> {
+    let result = runner::run_part(day, input, part)?;
+    if !explain {
+        report(format, &result, label);
+        return Ok(());
+    }
+
+    let explanation = day.solver.explain(input, part)?;
+    match format {
+        Format::Text => {
+            print!("{}", explanation);
+            println!("{}: {}", label, result.answer);
+        }
+        Format::Json => print_json(&Explained {
+            result: &result,
+            explanation: &explanation,
+        }),
+    }
+    Ok(())
+}
+
+fn find_day(config: &Config, day: u32) -> Result<Day> {
+    config
+        .find(day)
+        .ok_or_else(|| Error::Validation(format!("no solver registered for day {}", day)))
+}
+
+// The day to `run`, reading digits in the vocabulary given for day 1. Other
+// days can't be given one; see `Args::check_usage`.
+fn run_day(config: &Config, day: u32, vocabulary: &VocabularyArgs) -> Result<Day> {
+    if !vocabulary.is_set() {
+        return find_day(config, day);
+    }
+
+    Ok(Day {
+        number: 1,
+        solver: Box::new(calibration::Day1 {
+            vocabulary: vocabulary.vocabulary()?,
+            ..calibration::Day1::default()
+        }),
+    })
+}
+
+// The input root comes from `--inputs`, then AOC_INPUT_ROOT, then the config
+// file.
+fn input_store(inputs: Option<PathBuf>, config: &Config) -> InputStore {
+    let store = InputStore::from_env();
+    let root = inputs.or_else(|| match std::env::var_os(store::ROOT_VAR) {
+        Some(_) => None,
+        None => config.inputs.clone(),
+    });
+
+    match root {
+        Some(root) => store.with_root(root),
+        None => store,
+    }
+}
+
+// Days selected with `--day` fall back to the input store when no path is
+// given; `-` still reads stdin.
+fn day_input(store: &InputStore, day: u32, input_path: Option<&str>) -> Result<String> {
+    match input_path {
+        Some(path) => input::read_input(Some(path)),
+        None => store.get(day),
+    }
+}
+
+fn solve(
+    format: Format,
+    day: &Day,
+    input_path: Option<&str>,
+    part: Part,
+    label: &str,
+    explain: bool,
+) -> Result<()> {
+    let input = input::read_input(input_path)?;
+    report_part(format, day, &input, part, label, explain)
+}
+
+// A row of `run-all`: a solved part, or a part or whole day that failed.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RunEntry {
+    Solved(RunResult),
+    Failed {
+        day: u32,
+        /// The part that failed, or none when the day had no input.
+        part: Option<Part>,
+        error: String,
+    },
+}
+
+impl fmt::Display for RunEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunEntry::Solved(result) => write!(
+                f,
+                "{:<5}{:<6}{:<20}{:>12}",
+                result.day,
+                result.part,
+                result.answer.to_string(),
+                format!("{:.3?}", result.elapsed)
+            ),
+            RunEntry::Failed {
+                day,
+                part: Some(part),
+                error,
+            } => write!(f, "{:<5}{:<6}error: {}", day, part, error),
+            RunEntry::Failed {
+                day,
+                part: None,
+                error,
+            } => write!(f, "{:<5}{:<6}{}", day, "-", error),
+        }
+    }
+}
+
+// Runs every day, with an entry for each part and for each day that had no
+// input, and the exit code.
+fn run_entries(store: &InputStore, config: &Config) -> (Vec<RunEntry>, u8) {
+    let mut code = 0;
+    let mut ran = 0;
+    let mut entries = Vec::new();
+
+    for DayOutcome { day, outcome } in runner::run_all(store, &config.days()) {
+        let results = match outcome {
+            Outcome::MissingInput(err) => {
+                if code == 0 {
+                    code = EXIT_IO;
+                }
+                entries.push(RunEntry::Failed {
+                    day,
+                    part: None,
+                    error: format!("missing input: {}", err),
+                });
+                continue;
+            }
+            Outcome::Read(results) => results,
+        };
+        ran += 1;
+
+        for (part, result) in Part::ALL.into_iter().zip(results) {
+            entries.push(match result {
+                Ok(result) => RunEntry::Solved(result),
+                Err(err) => {
+                    if code == 0 {
+                        code = exit_code(&err);
+                    }
+                    RunEntry::Failed {
+                        day,
+                        part: Some(part),
+                        error: err.to_string(),
+                    }
+                }
+            });
+        }
+    }
+
+    if ran == 0 {
+        log::error!("no day had an input to run");
+        code = EXIT_IO;
+    }
+    (entries, code)
+}
+
+// Solves every day with a cached input. A missing input doesn't stop the
+// others, but still fails the run.
+fn run_all(format: Format, store: &InputStore, config: &Config) -> u8 {
+    let (entries, code) = run_entries(store, config);
+    match format {
+        Format::Text => {
+            println!("{:<5}{:<6}{:<20}{:>12}", "Day", "Part", "Answer", "Time");
+            for entry in &entries {
+                println!("{}", entry);
+            }
+        }
+        Format::Json => print_json(&entries),
+    }
+
+    code
+}
+
+#[derive(Serialize)]
+struct CheckResult {
+    day: u32,
+    /// Why the input was rejected or couldn't be read, if it was.
+    error: Option<String>,
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            None => write!(f, "Day {}: ok", self.day),
+            Some(error) => write!(f, "Day {}: {}", self.day, error),
+        }
+    }
+}
 
-            for game in games {
-                if game.possible_for(red, blue, green) {
-                    id_sum += game.id;
+// The result of checking one day's input, and its exit code.
+fn check_result(day: u32, checked: Result<()>) -> (CheckResult, u8) {
+    let result = CheckResult {
+        day,
+        error: checked.as_ref().err().map(|err| err.to_string()),
+    };
+    (result, checked.map_or_else(|err| exit_code(&err), |()| 0))
+}
+
+// Checks every cached input. Days without one are reported, and fail the
+// check.
+fn check_all(format: Format, store: &InputStore, config: &Config) -> u8 {
+    let mut code = 0;
+    let mut checked_any = false;
+    let mut results = Vec::new();
+
+    for DayOutcome { day, outcome } in runner::check_all(store, &config.days()) {
+        let (result, day_code) = match outcome {
+            Outcome::MissingInput(err) => {
+                let result = CheckResult {
+                    day,
+                    error: Some(format!("missing input: {}", err)),
+                };
+                (result, EXIT_IO)
+            }
+            Outcome::Read(checked) => {
+                checked_any = true;
+                check_result(day, checked)
+            }
+        };
+
+        if format == Format::Text {
+            println!("{}", result);
+        }
+        if code == 0 {
+            code = day_code;
+        }
+        results.push(result);
+    }
+
+    if format == Format::Json {
+        print_json(&results);
+    }
+    if !checked_any {
+        log::error!("no day had an input to check");
+        return EXIT_IO;
+    }
+
+    code
+}
+
+fn verify(
+    format: Format,
+    answers_path: PathBuf,
+    store: &InputStore,
+    config: &Config,
+) -> Result<bool> {
+    let answers = Answers::load(&answers_path)?;
+    let verifications = answers::verify(&answers, &config.days(), store)
+        .map_err(|err| err.in_file(&answers_path))?;
+    let succeeded = !verifications.iter().any(Verification::failed);
+
+    match format {
+        Format::Text => {
+            for Verification { day, part, status } in &verifications {
+                match status {
+                    Status::Pass => println!("PASS    day {} part {}", day, part),
+                    Status::Fail { expected, actual } => println!(
+                        "FAIL    day {} part {}: expected {}, got {}",
+                        day, part, expected, actual
+                    ),
+                    Status::Error { message } => {
+                        println!("FAIL    day {} part {}: {}", day, part, message)
+                    }
+                    Status::Missing => println!("MISSING day {} part {}", day, part),
                 }
             }
+        }
+        Format::Json => print_json(&verifications),
+    }
+
+    Ok(succeeded)
+}
+
+#[derive(Serialize)]
+struct SubmitReport<'a> {
+    day: u32,
+    part: Part,
+    answer: &'a Answer,
+    verdict: Verdict,
+}
+
+fn submit_answer(
+    format: Format,
+    day: &Day,
+    input: &str,
+    part: Part,
+    history_path: PathBuf,
+    answers_path: PathBuf,
+) -> Result<bool> {
+    let answer = runner::run_part(day, input, part)?.answer;
+
+    let mut history = History::load(&history_path)?;
+    let verdict = submit::submit(
+        &submit::HttpClient::from_env(),
+        &mut history,
+        store::YEAR,
+        day.number,
+        part,
+        &answer,
+    )?;
+    history.save(&history_path)?;
+
+    if verdict == Verdict::Correct {
+        submit::record_correct(&answers_path, day.number, part, &answer)?;
+    }
+
+    match format {
+        Format::Text => println!("Day {} Part {}: {} is {}", day.number, part, answer, verdict),
+        Format::Json => print_json(&SubmitReport {
+            day: day.number,
+            part,
+            answer: &answer,
+            verdict,
+        }),
+    }
+
+    Ok(verdict == Verdict::Correct)
+}
+
+fn print_bench(format: Format, report: &BenchReport) {
+    if format == Format::Json {
+        print_json(report);
+        return;
+    }
+
+    println!(
+        "{:<20}{:>14}{:>14}{:>14}",
+        format!("Day {} ({} runs)", report.day, report.runs),
+        "min",
+        "median",
+        "max"
+    );
+
+    for (stage, stats) in [
+        ("parse", &report.parse),
+        ("part 1", &report.part1),
+        ("part 2", &report.part2),
+    ] {
+        println!(
+            "{:<20}{:>14}{:>14}{:>14}",
+            stage,
+            format!("{:.3?}", stats.min),
+            format!("{:.3?}", stats.median),
+            format!("{:.3?}", stats.max)
+        );
+    }
+}
 
-            println!("Sum of Valid IDs: {}", id_sum);
-        },
+fn print_calibration_report(format: Format, style: ReportStyle, reports: &[LineReport]) {
+    if format == Format::Json {
+        print_json(&reports);
+        return;
+    }
+
+    let value = |report: &LineReport| match report.value {
+        Some(value) => value.to_string(),
+        None => "-".to_string(),
+    };
 
-        Command::Day2Part2 { input_path } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let games = stone_game::StoneGame::parse_lines(&input);
-            let mut power_sum = 0;
+    match style {
+        ReportStyle::Table => {
+            let mut table = Explanation::new(&["line", "used", "value", "tokens"]);
+            for report in reports {
+                let used: Vec<&str> =
+                    report.used.iter().map(|token| report.matched(token)).collect();
+                let tokens: Vec<String> = report
+                    .tokens
+                    .iter()
+                    .map(|token| {
+                        format!("{}@{}..{}", report.matched(token), token.start, token.end)
+                    })
+                    .collect();
 
-            for game in games {
-                power_sum += game.power();
+                table.push_row(vec![
+                    report.line.to_string(),
+                    if used.is_empty() { "-".to_string() } else { used.join(" ") },
+                    value(report),
+                    tokens.join(" "),
+                ]);
             }
+            print!("{}", table);
+        }
+        ReportStyle::Highlight => {
+            let color = std::io::stdout().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+            for report in reports {
+                let text = if color { report.highlighted() } else { report.bracketed() };
+                println!("{:>4}  {}  = {}", report.line, text, value(report));
+            }
+        }
+    }
+}
 
-            println!("Sum of Power: {}", power_sum);
-        },
+// Re-runs `part` of `day` each time its input file changes, printing the
+// new answer next to the previous one. Runs until interrupted.
+fn watch_day(
+    format: Format,
+    store: &InputStore,
+    solver: Day,
+    part: Part,
+    input_path: Option<&str>,
+    interval: Duration,
+) -> Result<u8> {
+    let day = solver.number;
+    let mut watcher = FileWatcher::new(match input_path {
+        Some(path) => PathBuf::from(path),
+        None => store.path(day),
+    });
+    let mut previous = None;
 
-        Command::Day3 { input_path } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let part_numbers = engine_schematic::part_numbers_in_corpus(&input);
+    loop {
+        let result = day_input(store, day, input_path)
+            .and_then(|input| runner::run_part(&solver, &input, part));
 
-            let mut pn_sum = 0;
-            for pn in part_numbers {
-                pn_sum += pn.number;
+        match result {
+            Ok(result) => {
+                match format {
+                    Format::Text => println!(
+                        "Day {} Part {}: {}",
+                        day,
+                        part,
+                        watch::describe_change(previous.as_ref(), &result.answer)
+                    ),
+                    Format::Json => print_json(&result),
+                }
+                previous = Some(result.answer);
             }
+            Err(err) => log::error!("{}", err),
+        }
 
-            println!("Sum of Part Numbers: {}", pn_sum);
-        },
+        log::info!("watching {} for changes", watcher.path().display());
+        while !watcher.changed() {
+            std::thread::sleep(interval);
+        }
+    }
+}
 
-        Command::Day3Part2 { input_path } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let gears = engine_schematic::gears_in_corpus(&input);
+fn run(args: Args) -> Result<u8> {
+    // These only describe the CLI, so they work even with a broken config.
+    match args.command {
+        Command::Completions { shell } => {
+            let mut command = Args::command();
+            clap_complete::generate(shell, &mut command, "aoc_2023", &mut std::io::stdout());
+            return Ok(0);
+        }
+        Command::Man => {
+            clap_mangen::Man::new(Args::command())
+                .render(&mut std::io::stdout())
+                .map_err(|err| Error::io("<stdout>", err))?;
+            return Ok(0);
+        }
+        _ => {}
+    }
 
-            let mut gear_ratio_sum = 0;
-            for gear in gears {
-                gear_ratio_sum += gear.ratio;
+    let config = Config::load()?;
+    let format = args.format.or(config.format).unwrap_or(Format::Text);
+    let store = input_store(args.inputs, &config);
+
+    match args.command {
+        Command::Run {
+            day,
+            check: true,
+            vocabulary,
+            input_path,
+            ..
+        } => {
+            let solver = run_day(&config, day, &vocabulary)?;
+            let input = day_input(&store, day, input_path.as_deref())?;
+            let (result, code) = check_result(day, solver.solver.check(&input));
+            match format {
+                Format::Text => println!("{}", result),
+                Format::Json => print_json(&result),
             }
+            return Ok(code);
+        }
+
+        Command::Run {
+            day,
+            part: Some(part),
+            watch: true,
+            interval,
+            vocabulary,
+            input_path,
+            ..
+        } => {
+            return watch_day(
+                format,
+                &store,
+                run_day(&config, day, &vocabulary)?,
+                part,
+                input_path.as_deref(),
+                Duration::from_millis(interval),
+            );
+        }
+
+        Command::Run {
+            day,
+            part: Some(part),
+            explain,
+            vocabulary,
+            input_path,
+            ..
+        } => {
+            let solver = run_day(&config, day, &vocabulary)?;
+            let input = day_input(&store, day, input_path.as_deref())?;
+            let label = format!("Day {} Part {}", day, part);
+            report_part(format, &solver, &input, part, &label, explain)?;
+        }
+
+        Command::Run { part: None, .. } => unreachable!("--part is required without --check"),
 
-            println!("Sum of Gear Ratios: {}", gear_ratio_sum);
-        },
+        Command::RunAll { check: false } => return Ok(run_all(format, &store, &config)),
 
-        Command::Day4 { input_path } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let cards = scratch_cards::cards_in_corpus(&input);
+        Command::RunAll { check: true } => return Ok(check_all(format, &store, &config)),
+
+        Command::Bench {
+            day,
+            runs,
+            input_path,
+        } => {
+            let input = day_input(&store, day, input_path.as_deref())?;
+            print_bench(format, &bench::bench(&find_day(&config, day)?, &input, runs)?);
+        }
+
+        Command::Repl { day, input_path } => {
+            let input = day_input(&store, day, input_path.as_deref())?;
+            let session = repl::Session::load(day, &input)?;
+            log::info!("day {} loaded; commands: {}", day, session.help());
+            repl::run(&session, std::io::stdin().lock(), std::io::stdout())
+                .map_err(|err| Error::io("<stdin>", err))?;
+        }
 
-            let mut score_sum = 0;
-            for card in cards {
-                score_sum += card.score();
+        Command::Verify { answers } => {
+            return verify(format, answers, &store, &config).map(exit_status);
+        }
+
+        Command::Submit {
+            day,
+            part,
+            history,
+            answers,
+            input_path,
+        } => {
+            let input = day_input(&store, day, input_path.as_deref())?;
+            return submit_answer(format, &find_day(&config, day)?, &input, part, history, answers)
+                .map(exit_status);
+        }
+
+        Command::NewDay { day, root, answers } => {
+            let written = scaffold::scaffold(&root, &answers, day)?;
+            match format {
+                Format::Text => {
+                    for path in &written {
+                        println!("wrote {}", path.display());
+                    }
+                }
+                Format::Json => print_json(&Scaffolded { day, written }),
+            }
+        }
+
+        Command::Day1 {
+            part,
+            vocabulary,
+            combine,
+            explain,
+            report,
+            input_path,
+        } => {
+            // Words don't count in part 1, so leave them out of its report too,
+            // but still reject bad ones.
+            let vocabulary = match (part, vocabulary.vocabulary()?) {
+                (Part::One, _) => DigitVocabulary::numerals(),
+                (Part::Two, vocabulary) => vocabulary,
+            };
+            if let Some(style) = report {
+                let input = input::read_input(input_path.as_deref())?;
+                let reports = DigitMatcher::new(&vocabulary)
+                    .with_combine(combine)
+                    .report(&input);
+                print_calibration_report(format, style, &reports);
+                return Ok(0);
             }
+            let day = Day {
+                number: 1,
+                solver: Box::new(calibration::Day1 {
+                    vocabulary,
+                    combine,
+                }),
+            };
+            solve(format, &day, input_path.as_deref(), part, "Calibration", explain)?;
+        }
+
+        Command::Day2 {
+            red,
+            green,
+            blue,
+            explain,
+            input_path,
+        } => {
+            let day = Day {
+                number: 2,
+                solver: Box::new(config.day2(red, green, blue)),
+            };
+            solve(format, &day, input_path.as_deref(), Part::One, "Sum of Valid IDs", explain)?;
+        }
+
+        Command::Day2Part2 { explain, input_path } => {
+            let day = find_day(&config, 2)?;
+            solve(format, &day, input_path.as_deref(), Part::Two, "Sum of Power", explain)?;
+        }
+
+        Command::Day3 { explain, input_path } => {
+            let day = find_day(&config, 3)?;
+            solve(format, &day, input_path.as_deref(), Part::One, "Sum of Part Numbers", explain)?;
+        }
+
+        Command::Day3Part2 { explain, input_path } => {
+            let day = find_day(&config, 3)?;
+            solve(format, &day, input_path.as_deref(), Part::Two, "Sum of Gear Ratios", explain)?;
+        }
 
-            println!("Sum of Scores: {}", score_sum);
-        },
+        Command::Day4 { explain, input_path } => {
+            let day = find_day(&config, 4)?;
+            solve(format, &day, input_path.as_deref(), Part::One, "Sum of Scores", explain)?;
+        }
 
-        Command::Day4Part2 { input_path } => {
-            let input = std::fs::read_to_string(input_path).unwrap();
-            let cards = scratch_cards::play_game(&input);
+        Command::Day4Part2 { explain, input_path } => {
+            let day = find_day(&config, 4)?;
+            solve(format, &day, input_path.as_deref(), Part::Two, "Number of Cards", explain)?;
+        }
 
-            println!("Number of Cards: {}", cards);
+        Command::Completions { .. } | Command::Man => {
+            unreachable!("handled before loading the config")
         }
     }
+
+    Ok(0)
+}
+
+// The exit code for a command that reports whether everything passed.
+fn exit_status(passed: bool) -> u8 {
+    if passed {
+        0
+    } else {
+        EXIT_FAILED
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Err(err) = args.check_usage() {
+        err.exit();
+    }
+    log::set_logger(&StderrLogger).expect("no other logger should be installed");
+    log::set_max_level(args.log_level());
+
+    match run(args) {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cli_definition() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn completions_list_commands_and_days() {
+        let mut completions = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut Args::command(), "aoc_2023", &mut completions);
+        let completions = String::from_utf8(completions).unwrap();
+
+        assert!(completions.contains("day2-part2"));
+        assert!(completions.contains("1 2 3 4"));
+    }
+
+    #[test]
+    fn verbosity_flags() {
+        let level = |args: &[&str]| {
+            let args = ["aoc_2023"].iter().chain(args).chain(&["run-all"]);
+            Args::try_parse_from(args).map(|args| args.log_level())
+        };
+
+        assert_eq!(level(&[]).unwrap(), LevelFilter::Warn);
+        assert_eq!(level(&["-q"]).unwrap(), LevelFilter::Error);
+        assert_eq!(level(&["-v"]).unwrap(), LevelFilter::Info);
+        assert_eq!(level(&["-vv"]).unwrap(), LevelFilter::Debug);
+        assert_eq!(level(&["-vvvv"]).unwrap(), LevelFilter::Trace);
+        assert!(level(&["-q", "-v"]).is_err());
+    }
+
+    #[test]
+    fn check_needs_no_part() {
+        assert!(Args::try_parse_from(["aoc_2023", "run", "--day", "3", "--check"]).is_ok());
+        assert!(Args::try_parse_from(["aoc_2023", "run", "--day", "3"]).is_err());
+        assert!(Args::try_parse_from(["aoc_2023", "run", "--day", "3", "--check", "--explain"])
+            .is_err());
+    }
+
+    #[test]
+    fn exit_codes_by_error() {
+        assert_eq!(exit_code(&Error::parse(1, 1, "bad")), EXIT_INVALID_INPUT);
+        assert_eq!(exit_code(&Error::Validation("empty".to_string())), EXIT_INVALID_INPUT);
+        assert_eq!(exit_code(&Error::io("day1.txt", std::io::ErrorKind::NotFound.into())), EXIT_IO);
+        assert_eq!(exit_code(&Error::network("https://example.com", "timed out")), EXIT_NETWORK);
+        assert_eq!(exit_code(&Error::Refused("too high".to_string())), EXIT_REFUSED);
+        assert_eq!(exit_code(&Error::parse(1, 1, "bad").in_file("aoc.toml")), EXIT_IO);
+        assert_eq!((exit_status(true), exit_status(false)), (0, EXIT_FAILED));
+    }
+
+    #[test]
+    fn day_must_be_registered() {
+        assert!(Args::try_parse_from(["aoc_2023", "run", "--day", "3", "--part", "1"]).is_ok());
+        assert!(Args::try_parse_from(["aoc_2023", "run", "--day", "25", "--part", "1"]).is_err());
+    }
+
+    #[test]
+    fn missing_inputs_fail_every_day_runs() {
+        let store = InputStore::new("no/such/inputs", 2023);
+        let config = Config::default();
+
+        assert_eq!(check_all(Format::Json, &store, &config), EXIT_IO);
+        assert_eq!(run_all(Format::Json, &store, &config), EXIT_IO);
+    }
+
+    #[test]
+    fn run_all_json_has_a_row_per_failure() {
+        let root = std::env::temp_dir().join("aoc_2023_run_all_json");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("day1.txt"), "two1nine\neightwothree").unwrap();
+        let store = InputStore::new(&root, 2023);
+
+        let (entries, code) = run_entries(&store, &Config::default());
+        let json: Vec<serde_json::Value> = entries
+            .iter()
+            .map(|entry| serde_json::to_value(entry).unwrap())
+            .collect();
+        assert_eq!(code, EXIT_INVALID_INPUT);
+        assert_eq!(json[0]["part"], 1);
+        assert!(json[0]["error"].as_str().unwrap().starts_with("invalid input"));
+        assert_eq!(json[1]["answer"], 29 + 83);
+        assert_eq!((&json[2]["day"], &json[2]["part"]), (&2.into(), &serde_json::Value::Null));
+        assert!(json[2]["error"].as_str().unwrap().starts_with("missing input"));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn new_days_are_puzzle_days() {
+        assert!(Args::try_parse_from(["aoc_2023", "new-day", "25"]).is_ok());
+        assert!(Args::try_parse_from(["aoc_2023", "new-day", "0"]).is_err());
+        assert!(Args::try_parse_from(["aoc_2023", "new-day", "26"]).is_err());
+    }
+
+    #[test]
+    fn vocabulary_is_only_for_day_1() {
+        let config = Config::default();
+        let words = |words: &str| VocabularyArgs {
+            language: None,
+            words: Some(words.to_string()),
+        };
+
+        assert!(run_day(&config, 1, &words("nul,een,twee")).is_ok());
+        assert!(matches!(run_day(&config, 1, &words("een,een")), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn misuse_is_a_usage_error() {
+        let usage = |args: &str| {
+            let args = Args::try_parse_from(args.split(' ')).and_then(|args| args.check_usage());
+            args.map_err(|err| err.exit_code())
+        };
+
+        assert_eq!(usage("aoc_2023 run --day 1 --part 1 --words a,b"), Ok(()));
+        assert_eq!(usage("aoc_2023 run --day 2 --part 1 --language german"), Err(2));
+        assert_eq!(usage("aoc_2023 run --day 2 --part 1 --watch -"), Err(2));
+        assert_eq!(usage("aoc_2023 repl --day 2 -"), Err(2));
+        assert_eq!(usage("aoc_2023 bench --day 2 --runs 0"), Err(2));
+        assert_eq!(usage("aoc_2023 bench --day 2 --runs 1"), Ok(()));
+    }
 }
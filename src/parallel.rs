@@ -0,0 +1,44 @@
+//! Independent work items that run on a thread pool when the `parallel`
+//! feature is enabled, and one after another otherwise.
+//!
+//! Either way results come back in the order of the items, so callers that
+//! report the first error or print in order behave identically.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Applies `f` to every item, keeping their order.
+#[cfg(feature = "parallel")]
+pub fn map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    items.par_iter().map(f).collect()
+}
+
+/// Applies `f` to every item, keeping their order.
+#[cfg(not(feature = "parallel"))]
+pub fn map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    items.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keeps_item_order() {
+        let items: Vec<u64> = (0..10_000).collect();
+        let squares = map(&items, |n| n * n);
+
+        assert_eq!(squares.len(), items.len());
+        assert!(items.iter().zip(&squares).all(|(n, square)| n * n == *square));
+    }
+}
@@ -0,0 +1,78 @@
+//! The list of solved days.
+
+use crate::calibration;
+use crate::engine_schematic;
+use crate::scratch_cards;
+use crate::solver::DynSolver;
+use crate::stone_game;
+
+/// A registered day and its solver.
+pub struct Day {
+    pub number: u32,
+    pub solver: Box<dyn DynSolver>,
+}
+
+/// Every registered day, in order.
+pub fn days() -> Vec<Day> {
+    vec![
+        Day {
+            number: 1,
+            solver: Box::new(calibration::Day1::default()),
+        },
+        Day {
+            number: 2,
+            solver: Box::new(stone_game::Day2::default()),
+        },
+        Day {
+            number: 3,
+            solver: Box::new(engine_schematic::Day3),
+        },
+        Day {
+            number: 4,
+            solver: Box::new(scratch_cards::Day4),
+        },
+    ]
+}
+
+/// The registered day with the given number.
+pub fn find(number: u32) -> Option<Day> {
+    days().into_iter().find(|day| day.number == number)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::Answer;
+    use crate::solver::Part;
+
+    #[test]
+    fn days_are_in_order() {
+        let numbers: Vec<u32> = days().iter().map(|day| day.number).collect();
+        assert!(numbers.starts_with(&[1, 2, 3, 4]));
+        assert!(numbers.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn find_day() {
+        let day = find(4).expect("day 4 should be registered");
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        ";
+
+        assert_eq!(day.solver.solve(input, Part::One).unwrap(), Answer::Number(10));
+        assert!(find(25).is_none());
+    }
+
+    #[test]
+    fn day1_parts_differ() {
+        let day = find(1).expect("day 1 should be registered");
+        let input = "
+        two1nine
+        eightwothree3
+        ";
+
+        assert_eq!(day.solver.solve(input, Part::One).unwrap(), Answer::Number(44));
+        assert_eq!(day.solver.solve(input, Part::Two).unwrap(), Answer::Number(112));
+    }
+}
@@ -0,0 +1,226 @@
+//! An interactive prompt for poking at a day's parsed input.
+//!
+//! A [`Session`] holds the parsed structures for one day and answers queries
+//! against them:
+//!
+//! - day 2: `game <id>`, `game <id> power`, `game <id> possible <r> <g> <b>`
+//! - day 3: `gear at <x>,<y>`, `pn near <x>,<y>`
+//! - day 4: `card <id>`, `card <id> matches`, `card <id> score`
+
+use std::io::BufRead;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::engine_schematic::Schematic;
+use crate::error::Error;
+use crate::error::Result;
+use crate::scratch_cards;
+use crate::scratch_cards::ScratchCard;
+use crate::solver::Solver;
+use crate::stone_game::StoneGame;
+
+/// The parsed input of the day being explored.
+pub enum Session {
+    Games(Vec<StoneGame>),
+    Schematic(Schematic),
+    Cards(Vec<ScratchCard>),
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::Validation(message.into())
+}
+
+fn parse_arg<T: FromStr>(word: Option<&str>, what: &str) -> Result<T> {
+    let Some(word) = word else {
+        return Err(invalid(format!("missing {}", what)));
+    };
+    word.parse()
+        .map_err(|_| invalid(format!("expected {}, found {:?}", what, word)))
+}
+
+// Parses an `x,y` position.
+fn parse_pos(word: Option<&str>) -> Result<(i32, i32)> {
+    let Some((x, y)) = word.and_then(|word| word.split_once(',')) else {
+        return Err(invalid("expected a position like 3,1"));
+    };
+    Ok((parse_arg(Some(x), "an x coordinate")?, parse_arg(Some(y), "a y coordinate")?))
+}
+
+impl Session {
+    /// Parses `input` for `day`.
+    pub fn load(day: u32, input: &str) -> Result<Session> {
+        match day {
+            2 => Ok(Session::Games(StoneGame::parse_lines(input)?)),
+            3 => Ok(Session::Schematic(Schematic::parse(input)?)),
+            4 => Ok(Session::Cards(scratch_cards::Day4.parse(input)?)),
+            _ => Err(invalid(format!("no repl commands for day {}", day))),
+        }
+    }
+
+    /// The commands this session understands.
+    pub fn help(&self) -> &'static str {
+        match self {
+            Session::Games(_) => "game <id> [power | possible <red> <green> <blue>]",
+            Session::Schematic(_) => "gear at <x>,<y> | pn near <x>,<y>",
+            Session::Cards(_) => "card <id> [matches | score]",
+        }
+    }
+
+    /// Runs one command and returns what to print.
+    pub fn execute(&self, command: &str) -> Result<String> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+
+        let output = match (self, name) {
+            (_, "help") => self.help().to_string(),
+
+            (Session::Games(games), "game") => {
+                let id = parse_arg(words.next(), "a game id")?;
+                let game = StoneGame::find(games, id)
+                    .ok_or_else(|| invalid(format!("no game {}", id)))?;
+
+                match words.next() {
+                    None => format!("{:#?}", game),
+                    Some("power") => game.power().to_string(),
+                    Some("possible") => {
+                        let red = parse_arg(words.next(), "a red count")?;
+                        let green = parse_arg(words.next(), "a green count")?;
+                        let blue = parse_arg(words.next(), "a blue count")?;
+                        game.possible_for(red, blue, green).to_string()
+                    }
+                    Some(other) => return Err(invalid(format!("unknown game query {:?}", other))),
+                }
+            }
+
+            (Session::Schematic(schematic), "gear") => {
+                if words.next() != Some("at") {
+                    return Err(invalid("expected gear at <x>,<y>"));
+                }
+                let pos = parse_pos(words.next())?;
+                match schematic.gear_at(pos) {
+                    Some(gear) => format!("{:#?}", gear),
+                    None => format!("no gear at {},{}", pos.0, pos.1),
+                }
+            }
+
+            (Session::Schematic(schematic), "pn") => {
+                if words.next() != Some("near") {
+                    return Err(invalid("expected pn near <x>,<y>"));
+                }
+                format!("{:#?}", schematic.part_numbers_near(parse_pos(words.next())?))
+            }
+
+            (Session::Cards(cards), "card") => {
+                let id = parse_arg(words.next(), "a card id")?;
+                let card = scratch_cards::find_card(cards, id)
+                    .ok_or_else(|| invalid(format!("no card {}", id)))?;
+
+                match words.next() {
+                    None => format!("{:#?}", card),
+                    Some("matches") => format!("{:?}", card.matching_numbers()),
+                    Some("score") => card.score().to_string(),
+                    Some(other) => return Err(invalid(format!("unknown card query {:?}", other))),
+                }
+            }
+
+            _ => return Err(invalid(format!("unknown command {:?}, try: {}", name, self.help()))),
+        };
+
+        if let Some(extra) = words.next() {
+            return Err(invalid(format!("unexpected {:?}", extra)));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Reads commands from `input` until it ends or `quit` is entered, writing
+/// each result (or error) to `output`.
+pub fn run(session: &Session, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        match line.trim() {
+            "" => {}
+            "quit" | "exit" => break,
+            command => match session.execute(command) {
+                Ok(result) => writeln!(output, "{}", result)?,
+                Err(err) => writeln!(output, "error: {}", err)?,
+            },
+        }
+
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn game_queries() {
+        let input = "
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        ";
+        let session = Session::load(2, input).unwrap();
+
+        assert!(session.execute("game 1").unwrap().contains("id: 1"));
+        assert_eq!(session.execute("game 1 power").unwrap(), "48");
+        assert_eq!(session.execute("game 3 possible 12 13 14").unwrap(), "false");
+        assert!(session.execute("game 2").is_err());
+        assert!(session.execute("game one").is_err());
+    }
+
+    #[test]
+    fn schematic_queries() {
+        let input = "
+        467..114..
+        ...*......
+        ..35..633.
+        ......#...
+        ";
+        let session = Session::load(3, input).unwrap();
+
+        assert!(session.execute("gear at 3,1").unwrap().contains("ratio: 16345"));
+        assert_eq!(session.execute("gear at 0,0").unwrap(), "no gear at 0,0");
+        assert!(session.execute("pn near 6,3").unwrap().contains("number: 633"));
+        assert!(session.execute("pn near 6").is_err());
+    }
+
+    #[test]
+    fn card_queries() {
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        ";
+        let session = Session::load(4, input).unwrap();
+
+        assert_eq!(session.execute("card 2 matches").unwrap(), "[32, 61]");
+        assert_eq!(session.execute("card 1 score").unwrap(), "8");
+        assert!(session.execute("card 1 matches please").is_err());
+        assert!(session.execute("game 1").is_err());
+    }
+
+    #[test]
+    fn run_commands() {
+        let session = Session::load(4, "Card 1: 41 48 83 | 83 41 6").unwrap();
+        let mut output = Vec::new();
+
+        run(&session, "card 1 score\n\nbogus\nquit\ncard 1\n".as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("> 2\n> > error: invalid input: unknown command \"bogus\""));
+        assert!(!output.contains("id: 1"));
+    }
+
+    #[test]
+    fn unsupported_day() {
+        assert!(matches!(Session::load(1, "1abc2"), Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,195 @@
+//! Timed execution of registered days.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+use log::info;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::parallel;
+use crate::registry::Day;
+use crate::solver::Answer;
+use crate::solver::Part;
+use crate::store::InputStore;
+
+/// The answer to one part of a day and how long it took.
+#[derive(Serialize)]
+pub struct RunResult {
+    pub day: u32,
+    pub part: Part,
+    pub answer: Answer,
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+}
+
+/// Serializes a duration as fractional milliseconds.
+pub fn serialize_millis<S: Serializer>(elapsed: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64() * 1000.0)
+}
+
+/// The conventional input location for `day`: `<inputs_dir>/day<N>.txt`.
+pub fn input_path(inputs_dir: &Path, day: u32) -> PathBuf {
+    inputs_dir.join(format!("day{}.txt", day))
+}
+
+/// Parses `input` and solves `part`, timing both.
+pub fn run_part(day: &Day, input: &str, part: Part) -> Result<RunResult> {
+    info!("day {} part {}: solving", day.number, part);
+    let start = Instant::now();
+    let answer = day.solver.solve(input, part)?;
+    let elapsed = start.elapsed();
+    info!("day {} part {}: {} in {:?}", day.number, part, answer, elapsed);
+
+    Ok(RunResult {
+        day: day.number,
+        part,
+        answer,
+        elapsed,
+    })
+}
+
+/// Runs both parts of `day`. Each part succeeds or fails on its own.
+pub fn run_day(day: &Day, input: &str) -> Vec<Result<RunResult>> {
+    Part::ALL
+        .iter()
+        .map(|part| run_part(day, input, *part))
+        .collect()
+}
+
+/// What came of one day in [`run_all`] or [`check_all`].
+#[derive(Debug)]
+pub enum Outcome<T> {
+    /// The day's input couldn't be read or fetched.
+    MissingInput(Error),
+    /// The day's input was read, and gave this.
+    Read(T),
+}
+
+/// One day of [`run_all`] or [`check_all`].
+#[derive(Debug)]
+pub struct DayOutcome<T> {
+    pub day: u32,
+    pub outcome: Outcome<T>,
+}
+
+/// Runs both parts of every day with its input from `store`. Inputs are
+/// fetched one at a time; the days themselves run concurrently with the
+/// `parallel` feature.
+pub fn run_all(store: &InputStore, days: &[Day]) -> Vec<DayOutcome<Vec<Result<RunResult>>>> {
+    let inputs: Vec<(&Day, Result<String>)> =
+        days.iter().map(|day| (day, store.get(day.number))).collect();
+    let results = parallel::map(&inputs, |(day, input)| match input {
+        Ok(input) => Some(run_day(day, input)),
+        Err(_) => None,
+    });
+
+    inputs
+        .into_iter()
+        .zip(results)
+        .map(|((day, input), results)| DayOutcome {
+            day: day.number,
+            outcome: match input {
+                Ok(_) => Outcome::Read(results.expect("days with an input are run")),
+                Err(err) => Outcome::MissingInput(err),
+            },
+        })
+        .collect()
+}
+
+/// Parses every day's input from `store` to check its format, without
+/// solving.
+pub fn check_all(store: &InputStore, days: &[Day]) -> Vec<DayOutcome<Result<()>>> {
+    days.iter()
+        .map(|day| DayOutcome {
+            day: day.number,
+            outcome: match store.get(day.number) {
+                Ok(input) => Outcome::Read(day.solver.check(&input)),
+                Err(err) => Outcome::MissingInput(err),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn input_path_convention() {
+        assert_eq!(
+            input_path(Path::new("inputs"), 2),
+            PathBuf::from("inputs/day2.txt")
+        );
+    }
+
+    #[test]
+    fn run_day_runs_both_parts() {
+        let day = registry::find(1).expect("day 1 should be registered");
+        let results: Vec<RunResult> =
+            run_day(&day, "1abc2\npqr3stu8vwx").into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].part, Part::One);
+        assert_eq!(results[0].answer, Answer::Number(50));
+        assert_eq!(results[1].part, Part::Two);
+        assert_eq!(results[1].day, 1);
+    }
+
+    #[test]
+    fn run_day_keeps_parts_apart() {
+        let day = registry::find(1).expect("day 1 should be registered");
+        let results = run_day(&day, "two1nine\neightwothree\nabcone2threexyz");
+
+        assert!(matches!(results[0], Err(Error::Validation(_))));
+        assert_eq!(results[1].as_ref().unwrap().answer, Answer::Number(29 + 83 + 13));
+    }
+
+    #[test]
+    fn run_and_check_every_day() {
+        let root = std::env::temp_dir().join("aoc_2023_runner");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("day1.txt"), "1abc2\npqr3stu8vwx").unwrap();
+        std::fs::write(root.join("day4.txt"), "Card 1: 41 | x").unwrap();
+        let store = InputStore::new(&root, 2023);
+        let days: Vec<Day> = [1, 3, 4].into_iter().filter_map(registry::find).collect();
+
+        let runs = run_all(&store, &days);
+        assert!(matches!(&runs[0].outcome, Outcome::Read(results) if results.len() == 2));
+        assert!(matches!(&runs[1].outcome, Outcome::MissingInput(Error::Io { .. })));
+        let Outcome::Read(results) = &runs[2].outcome else {
+            panic!("day 4 has an input");
+        };
+        assert!(results.iter().all(|result| matches!(result, Err(Error::Parse { .. }))));
+
+        let checks = check_all(&store, &days);
+        let days: Vec<u32> = checks.iter().map(|check| check.day).collect();
+        assert_eq!(days, vec![1, 3, 4]);
+        assert!(matches!(checks[0].outcome, Outcome::Read(Ok(()))));
+        assert!(matches!(checks[1].outcome, Outcome::MissingInput(_)));
+        assert!(matches!(checks[2].outcome, Outcome::Read(Err(_))));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn run_result_json() {
+        let result = RunResult {
+            day: 3,
+            part: Part::Two,
+            answer: Answer::Number(467835),
+            elapsed: Duration::from_micros(1500),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"day":3,"part":2,"answer":467835,"elapsed_ms":1.5}"#
+        );
+    }
+}
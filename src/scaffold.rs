@@ -0,0 +1,313 @@
+//! Generates the skeleton for a new day: a solver module with an example
+//! test, its `mod` line, its registry entry and an empty expected-answers
+//! entry.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::Error;
+use crate::error::Result;
+
+// Unsolved parts fail with an error rather than panicking, so `run-all` and
+// `verify` keep going.
+const MODULE_TEMPLATE: &str = r#"//! Day {day}.
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::solver::Solver;
+
+/// Day {day} solver.
+pub struct Day{day};
+
+impl Solver for Day{day} {
+    type Parsed = Vec<String>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        Ok(input.trim().lines().map(|line| line.trim().to_string()).collect())
+    }
+
+    fn part1(&self, _lines: &Vec<String>) -> Result<i64> {
+        Err(Error::Validation("day {day} part 1 is not solved yet".to_string()))
+    }
+
+    fn part2(&self, _lines: &Vec<String>) -> Result<i64> {
+        Err(Error::Validation("day {day} part 2 is not solved yet".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[ignore = "fill in the example input and answers"]
+    fn day{day}_solver_example() {
+        let input = "
+        ";
+
+        let parsed = Day{day}.parse(input).unwrap();
+        assert_eq!(Day{day}.part1(&parsed).unwrap(), 0);
+        assert_eq!(Day{day}.part2(&parsed).unwrap(), 0);
+    }
+}
+"#;
+
+const REGISTRY_TEMPLATE: &str = "        Day {
+            number: {day},
+            solver: Box::new(day{day}::Day{day}),
+        },
+";
+
+// How each entry in `registry::days` starts, followed by its day number.
+const ENTRY_START: &str = "        Day {\n            number: ";
+
+// The days a puzzle is published on.
+const DAYS: std::ops::RangeInclusive<u32> = 1..=25;
+
+pub fn module_name(day: u32) -> String {
+    format!("day{}", day)
+}
+
+/// The source of a new day's solver module.
+pub fn module_source(day: u32) -> String {
+    MODULE_TEMPLATE.replace("{day}", &day.to_string())
+}
+
+// Inserts `line` into the sorted block of lines starting with `prefix`.
+fn insert_sorted(source: &str, prefix: &str, line: &str) -> Result<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let block: Vec<usize> = (0..lines.len())
+        .filter(|&i| lines[i].starts_with(prefix))
+        .collect();
+
+    let Some(&last) = block.last() else {
+        return Err(Error::Validation(format!("no {:?} lines to extend", prefix)));
+    };
+
+    if block.iter().any(|&i| lines[i] == line) {
+        return Err(Error::Validation(format!("{:?} is already present", line)));
+    }
+
+    let position = block
+        .iter()
+        .copied()
+        .find(|&i| lines[i] > line)
+        .unwrap_or(last + 1);
+
+    let mut updated: Vec<&str> = lines;
+    updated.insert(position, line);
+    Ok(updated.join("\n") + "\n")
+}
+
+/// Adds `pub mod day<N>;` to the library root.
+pub fn add_module(lib_source: &str, day: u32) -> Result<String> {
+    insert_sorted(lib_source, "pub mod ", &format!("pub mod {};", module_name(day)))
+}
+
+/// Imports the new module and adds its solver to `registry::days`, keeping
+/// the days in order.
+pub fn register_day(registry_source: &str, day: u32) -> Result<String> {
+    if registry_source.contains(&format!("number: {},", day)) {
+        return Err(Error::Validation(format!("day {} is already registered", day)));
+    }
+
+    let source = insert_sorted(
+        registry_source,
+        "use crate::",
+        &format!("use crate::{};", module_name(day)),
+    )?;
+
+    let Some(end) = source.find("\n    ]\n") else {
+        return Err(Error::Validation("could not find the end of the day list".to_string()));
+    };
+
+    let later = source.match_indices(ENTRY_START).find(|&(start, _)| {
+        let number = source[start + ENTRY_START.len()..].split(',').next();
+        number.and_then(|number| number.parse::<u32>().ok()).is_some_and(|number| number > day)
+    });
+    let position = later.map_or(end + 1, |(start, _)| start);
+
+    let entry = REGISTRY_TEMPLATE.replace("{day}", &day.to_string());
+    Ok(format!("{}{}{}", &source[..position], entry, &source[position..]))
+}
+
+/// Appends an empty `[day<N>]` table to an answers file.
+pub fn add_answers_entry(answers_source: &str, day: u32) -> String {
+    let header = format!("[{}]", module_name(day));
+    if answers_source.lines().any(|line| line.trim() == header) {
+        return answers_source.to_string();
+    }
+
+    let mut source = answers_source.trim_end().to_string();
+    if !source.is_empty() {
+        source.push_str("\n\n");
+    }
+    source.push_str(&header);
+    source.push('\n');
+    source
+}
+
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|err| Error::io(path, err))
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents).map_err(|err| Error::io(path, err))
+}
+
+/// Scaffolds day `day` in the crate at `root`, returning the files written.
+pub fn scaffold(root: &Path, answers_path: &Path, day: u32) -> Result<Vec<PathBuf>> {
+    if !DAYS.contains(&day) {
+        return Err(Error::Validation(format!("there is no day {}; days run 1 to 25", day)));
+    }
+
+    let src = root.join("src");
+    let module_path = src.join(format!("{}.rs", module_name(day)));
+    if module_path.exists() {
+        return Err(Error::Validation(format!("{} already exists", module_path.display())));
+    }
+
+    let lib_path = src.join("lib.rs");
+    let registry_path = src.join("registry.rs");
+    let lib_source = add_module(&read(&lib_path)?, day)?;
+    let registry_source = register_day(&read(&registry_path)?, day)?;
+    let answers_source = match std::fs::read_to_string(answers_path) {
+        Ok(source) => source,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(Error::io(answers_path, err)),
+    };
+
+    write(&module_path, &module_source(day))?;
+    write(&lib_path, &lib_source)?;
+    write(&registry_path, &registry_source)?;
+    write(answers_path, &add_answers_entry(&answers_source, day))?;
+
+    Ok(vec![
+        module_path,
+        lib_path,
+        registry_path,
+        answers_path.to_path_buf(),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::answers::Answers;
+    use crate::solver::Part;
+
+    const LIB: &str = "//! Docs.
+
+pub mod calibration;
+pub mod engine_schematic;
+pub mod stone_game;
+";
+
+    const REGISTRY: &str = "use crate::calibration;
+use crate::solver::DynSolver;
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day {
+            number: 1,
+            solver: Box::new(calibration::Day1),
+        },
+    ]
+}
+";
+
+    #[test]
+    fn module_source_for_day() {
+        let source = module_source(5);
+        assert!(source.starts_with("//! Day 5.\n"));
+        assert!(source.contains("pub struct Day5;"));
+        assert!(source.contains("impl Solver for Day5 {"));
+        assert!(source.contains("fn day5_solver_example()"));
+        assert!(!source.contains("{day}"));
+        assert!(!source.contains("todo!"));
+        assert!(source.contains("\"day 5 part 2 is not solved yet\""));
+    }
+
+    #[test]
+    fn add_module_sorted() {
+        let source = add_module(LIB, 5).unwrap();
+        assert!(source.contains("pub mod calibration;\npub mod day5;\npub mod engine_schematic;"));
+        assert!(add_module(&source, 5).is_err());
+    }
+
+    #[test]
+    fn register_new_day() {
+        let source = register_day(REGISTRY, 5).unwrap();
+        assert_eq!(
+            source,
+            "use crate::calibration;
+use crate::day5;
+use crate::solver::DynSolver;
+
+pub fn days() -> Vec<Day> {
+    vec![
+        Day {
+            number: 1,
+            solver: Box::new(calibration::Day1),
+        },
+        Day {
+            number: 5,
+            solver: Box::new(day5::Day5),
+        },
+    ]
+}
+"
+        );
+        assert!(register_day(&source, 5).is_err());
+    }
+
+    #[test]
+    fn register_days_in_order() {
+        let source = register_day(REGISTRY, 10).unwrap();
+        let source = register_day(&source, 5).unwrap();
+
+        let numbers: Vec<&str> = source
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("number: "))
+            .collect();
+        assert_eq!(numbers, vec!["1,", "5,", "10,"]);
+        assert!(source.contains("use crate::day10;\nuse crate::day5;\n"));
+    }
+
+    #[test]
+    fn answers_entry() {
+        let source = add_answers_entry("[day1]\npart1 = 142\n", 5);
+        assert_eq!(source, "[day1]\npart1 = 142\n\n[day5]\n");
+        assert_eq!(add_answers_entry(&source, 5), source);
+        assert_eq!(add_answers_entry("", 5), "[day5]\n");
+
+        let answers = Answers::parse(&source).unwrap();
+        assert_eq!(answers.expected(5, Part::One), None);
+    }
+
+    #[test]
+    fn scaffold_crate() {
+        let root = std::env::temp_dir().join("aoc_2023_scaffold");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src").join("lib.rs"), LIB).unwrap();
+        std::fs::write(root.join("src").join("registry.rs"), REGISTRY).unwrap();
+
+        let answers_path = root.join("answers.toml");
+        let written = scaffold(&root, &answers_path, 5).unwrap();
+
+        assert_eq!(written.len(), 4);
+        assert_eq!(
+            std::fs::read_to_string(root.join("src").join("day5.rs")).unwrap(),
+            module_source(5)
+        );
+        assert_eq!(std::fs::read_to_string(&answers_path).unwrap(), "[day5]\n");
+        assert!(scaffold(&root, &answers_path, 5).is_err());
+        assert!(matches!(scaffold(&root, &answers_path, 0), Err(Error::Validation(_))));
+        assert!(matches!(scaffold(&root, &answers_path, 26), Err(Error::Validation(_))));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,5 +1,13 @@
+//! Day 4: Scratchcards.
+//!
+//! Each card lists winning numbers and the numbers you have. Part 1 scores
+//! cards by doubling per match, part 2 counts the cards you end up with when
+//! matches win copies of the following cards.
+
 use std::collections::BinaryHeap;
 
+use log::debug;
+use log::warn;
 use nom::IResult;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_while1;
@@ -9,7 +17,15 @@ use nom::multi::many1;
 use nom::multi::many_till;
 use nom::sequence::tuple;
 
-#[derive(Clone)]
+use crate::error::try_map_lines;
+use crate::error::Error;
+use crate::error::Result;
+use crate::solver::Explanation;
+use crate::solver::Part;
+use crate::solver::Solver;
+
+/// A card line, e.g. `Card 1: 41 48 83 | 83 86 6`.
+#[derive(Clone, Debug)]
 pub struct ScratchCard {
     id: usize,
     winning_numbers: Vec<u32>,
@@ -39,9 +55,15 @@ impl PartialEq for ScratchCard {
 
 impl ScratchCard {
     fn parse(input: &str) -> IResult<&str, ScratchCard> {
-        let number_parser = || take_while1(|c: char| c.is_digit(10));
-        let (input, (_, _, _, id, _, _)) = tuple((multispace0, tag("Card"), multispace0, number_parser(), tag(":"), multispace0))(input)?;
-        let id = id.parse::<usize>().unwrap();
+        let number_parser = || take_while1(|c: char| c.is_ascii_digit());
+        let (input, (_, _, _, id, _, _)) = tuple((
+            multispace0,
+            tag("Card"),
+            multispace0,
+            map_res(number_parser(), |id: &str| id.parse::<usize>()),
+            tag(":"),
+            multispace0
+        ))(input)?;
         
         let (input, (winning_numbers, _)) = many_till(
             map_res(
@@ -66,6 +88,38 @@ impl ScratchCard {
         Ok((input, ScratchCard { id, winning_numbers, my_numbers, num_matching }))
     }
 
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn winning_numbers(&self) -> &[u32] {
+        &self.winning_numbers
+    }
+
+    pub fn my_numbers(&self) -> &[u32] {
+        &self.my_numbers
+    }
+
+    fn has_repeated_winning_number(&self) -> bool {
+        let numbers = &self.winning_numbers;
+        (0..numbers.len()).any(|i| numbers[..i].contains(&numbers[i]))
+    }
+
+    /// How many winning numbers appear among your numbers.
+    pub fn num_matching(&self) -> usize {
+        self.num_matching
+    }
+
+    /// The winning numbers that appear among your numbers.
+    pub fn matching_numbers(&self) -> Vec<u32> {
+        self.winning_numbers
+            .iter()
+            .copied()
+            .filter(|n| self.my_numbers.contains(n))
+            .collect()
+    }
+
+    /// 1 point for the first match, doubled for every match after it.
     pub fn score(&self) -> u32 {
         let matching_count = self.num_matching;
 
@@ -78,18 +132,65 @@ impl ScratchCard {
     }
 }
 
-pub fn cards_in_corpus(input: &str) -> Vec<ScratchCard> {
-    let mut result = Vec::new();
-    for line in input.lines() {
-        if let Ok((_, card)) = ScratchCard::parse(line) {
-            result.push(card);
+/// Parses every non-blank line of `input` as a card.
+pub fn cards_in_corpus(input: &str) -> Result<Vec<ScratchCard>> {
+    let cards = try_map_lines(input, |line_number, indent, line| match ScratchCard::parse(line) {
+        Ok(("", card)) => {
+            if card.has_repeated_winning_number() {
+                warn!(
+                    "line {}: card {} repeats a winning number, which counts as a match each time",
+                    line_number, card.id
+                );
+            }
+            Ok(card)
+        }
+        Ok((remaining, _)) => {
+            Err(Error::parse_at(line, line.len() - remaining.len(), "unexpected trailing input")
+                .at_line(line_number, indent))
+        }
+        Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+            Err(Error::parse_at(line, line.len() - err.input.len(), "malformed scratch card")
+                .at_line(line_number, indent))
+        }
+        Err(nom::Err::Incomplete(_)) => {
+            Err(Error::parse(line_number, indent + 1, "incomplete scratch card"))
+        }
+    })?;
+
+    debug!("parsed {} cards", cards.len());
+    Ok(cards)
+}
+
+/// The card with the given id.
+pub fn find_card(cards: &[ScratchCard], id: usize) -> Option<&ScratchCard> {
+    cards.iter().find(|card| card.id == id)
+}
+
+// `play_cards` looks cards up by id, so they have to be numbered 1..=n in
+// order for the copies to land on the right cards.
+fn check_card_order(cards: &[ScratchCard]) -> Result<()> {
+    for (index, card) in cards.iter().enumerate() {
+        if card.id != index + 1 {
+            return Err(Error::Validation(format!(
+                "expected card {} but found card {}",
+                index + 1,
+                card.id
+            )));
         }
     }
-    result
+    Ok(())
+}
+
+/// Parses `input` and counts the cards held once every copy is played.
+pub fn play_game(input: &str) -> Result<usize> {
+    let cards = cards_in_corpus(input)?;
+    check_card_order(&cards)?;
+    Ok(play_cards(&cards))
 }
 
-pub fn play_game(input: &str) -> usize {
-    let original_cards = cards_in_corpus(input);
+/// Counts the cards held once every copy is played. Cards must be numbered
+/// 1..=n in order.
+pub fn play_cards(original_cards: &[ScratchCard]) -> usize {
     let mut count: usize = 0;
     let mut working_heap: BinaryHeap<&ScratchCard> = BinaryHeap::new();
 
@@ -104,7 +205,7 @@ pub fn play_game(input: &str) -> usize {
             let end_index = original_card_index + matching_count;
 
             while original_card_index < original_cards.len() && original_card_index < end_index {
-                let original_card = original_cards.get(original_card_index as usize).expect("index should be valid");
+                let original_card = original_cards.get(original_card_index).expect("index should be valid");
                 working_heap.push(original_card);
                 original_card_index += 1;
             }
@@ -114,6 +215,62 @@ pub fn play_game(input: &str) -> usize {
     count
 }
 
+/// How many copies of each card are held once every copy is played,
+/// including the original. Cards must be numbered 1..=n in order.
+pub fn card_copies(cards: &[ScratchCard]) -> Vec<usize> {
+    let mut copies = vec![1; cards.len()];
+
+    for (index, card) in cards.iter().enumerate() {
+        let end = (index + 1 + card.num_matching).min(cards.len());
+        for won in index + 1..end {
+            copies[won] += copies[index];
+        }
+    }
+
+    copies
+}
+
+/// Day 4 solver.
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Parsed = Vec<ScratchCard>;
+    type Part1 = u32;
+    type Part2 = usize;
+
+    fn parse(&self, input: &str) -> Result<Vec<ScratchCard>> {
+        let cards = cards_in_corpus(input)?;
+        check_card_order(&cards)?;
+        Ok(cards)
+    }
+
+    fn part1(&self, cards: &Vec<ScratchCard>) -> Result<u32> {
+        let mut score_sum = 0;
+        for card in cards {
+            score_sum += card.score();
+        }
+        Ok(score_sum)
+    }
+
+    fn part2(&self, cards: &Vec<ScratchCard>) -> Result<usize> {
+        Ok(play_cards(cards))
+    }
+
+    // Scores and copies are both shown, whichever part is asked for.
+    fn explain(&self, cards: &Vec<ScratchCard>, _part: Part) -> Result<Explanation> {
+        let mut explanation = Explanation::new(&["card", "matches", "score", "copies"]);
+        for (card, copies) in cards.iter().zip(card_copies(cards)) {
+            explanation.push_row(vec![
+                card.id.to_string(),
+                format!("{:?}", card.matching_numbers()),
+                card.score().to_string(),
+                copies.to_string(),
+            ]);
+        }
+        Ok(explanation)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -138,7 +295,7 @@ mod test {
         Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
         ";
         
-        let cards = cards_in_corpus(input);
+        let cards = cards_in_corpus(input).unwrap();
         assert_eq!(cards.len(), 6);
 
 
@@ -178,7 +335,7 @@ mod test {
         Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
         ";
         
-        let cards = cards_in_corpus(input);
+        let cards = cards_in_corpus(input).unwrap();
         assert_eq!(cards.len(), 6);
 
         assert!(cards[0].score() == 8);
@@ -200,7 +357,77 @@ mod test {
         Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
         ";
         
-        let cards = play_game(input);
+        let cards = play_game(input).unwrap();
         assert_eq!(cards, 30);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn day4_solver_example() {
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+        ";
+
+        let cards = Day4.parse(input).unwrap();
+        assert_eq!(Day4.part1(&cards).unwrap(), 13);
+        assert_eq!(Day4.part2(&cards).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_find_card() {
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        ";
+
+        let cards = cards_in_corpus(input).unwrap();
+        assert_eq!(find_card(&cards, 1).unwrap().matching_numbers(), vec![48, 83, 86, 17]);
+        assert_eq!(find_card(&cards, 2).unwrap().matching_numbers(), vec![32, 61]);
+        assert!(find_card(&cards, 3).is_none());
+    }
+
+    #[test]
+    fn test_card_copies() {
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+        ";
+
+        let cards = cards_in_corpus(input).unwrap();
+        let copies = card_copies(&cards);
+        assert_eq!(copies, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(copies.iter().sum::<usize>(), play_cards(&cards));
+    }
+
+    #[test]
+    fn test_malformed_card() {
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 1x
+        ";
+
+        match cards_in_corpus(input) {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (3, 56)),
+            Err(other) => panic!("unexpected error {:?}", other),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_cards_out_of_order() {
+        let input = "
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 3: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        ";
+
+        assert!(matches!(play_game(input), Err(Error::Validation(_))));
+    }
+}
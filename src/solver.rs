@@ -0,0 +1,316 @@
+//! The interface every day implements.
+
+use std::any::Any;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::numbered_lines;
+use crate::error::Error;
+use crate::error::Result;
+
+/// One of the two halves of a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => f.pad("1"),
+            Part::Two => f.pad("2"),
+        }
+    }
+}
+
+impl Serialize for Part {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Part::One => serializer.serialize_u8(1),
+            Part::Two => serializer.serialize_u8(2),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Part {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        match u8::deserialize(deserializer)? {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            other => Err(serde::de::Error::custom(format!("part must be 1 or 2, got {}", other))),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            other => Err(format!("part must be 1 or 2, got {}", other)),
+        }
+    }
+}
+
+/// A puzzle answer, independent of the type the solver computed it as.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Self {
+        Answer::Number(value.into())
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        Answer::Number(value.into())
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Answer::Number(value)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Number(value as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+/// A per-item breakdown of how a day's answers come together, as a table
+/// with one row per line, game, part number or card.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Explanation {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Explanation {
+    pub fn new(columns: &[&str]) -> Explanation {
+        Explanation {
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths: Vec<usize> = self.columns.iter().map(|column| column.len()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        for row in std::iter::once(&self.columns).chain(&self.rows) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A day's solution: a parser for the puzzle input and the two parts that
+/// answer questions about the parsed input.
+pub trait Solver {
+    type Parsed;
+    type Part1: Into<Answer>;
+    type Part2: Into<Answer>;
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed>;
+    fn part1(&self, parsed: &Self::Parsed) -> Result<Self::Part1>;
+    fn part2(&self, parsed: &Self::Parsed) -> Result<Self::Part2>;
+
+    /// Breaks the answer to `part` down item by item. Days without a
+    /// breakdown return an empty table.
+    fn explain(&self, _parsed: &Self::Parsed, _part: Part) -> Result<Explanation> {
+        Ok(Explanation::default())
+    }
+}
+
+/// Object-safe view of a [`Solver`] so days with different input and answer
+/// types can live side by side in the registry. Solvers are shared between
+/// threads when days run in parallel.
+pub trait DynSolver: Send + Sync {
+    /// Parses the input into the solver's own (type-erased) representation.
+    /// Input without any non-blank lines is rejected.
+    fn parse_input(&self, input: &str) -> Result<Box<dyn Any>>;
+    /// Solves `part` from the output of [`DynSolver::parse_input`].
+    fn solve_part(&self, parsed: &dyn Any, part: Part) -> Result<Answer>;
+    /// Explains `part` from the output of [`DynSolver::parse_input`].
+    fn explain_parsed(&self, parsed: &dyn Any, part: Part) -> Result<Explanation>;
+
+    /// Parses `input` without solving it, to check it's in the day's format.
+    fn check(&self, input: &str) -> Result<()> {
+        self.parse_input(input).map(|_| ())
+    }
+
+    /// Parses `input` and solves `part`.
+    fn solve(&self, input: &str, part: Part) -> Result<Answer> {
+        let parsed = self.parse_input(input)?;
+        self.solve_part(parsed.as_ref(), part)
+    }
+
+    /// Parses `input` and explains the answer to `part`.
+    fn explain(&self, input: &str, part: Part) -> Result<Explanation> {
+        let parsed = self.parse_input(input)?;
+        self.explain_parsed(parsed.as_ref(), part)
+    }
+}
+
+impl<S> DynSolver for S
+where
+    S: Solver + Send + Sync,
+    S::Parsed: 'static,
+{
+    fn parse_input(&self, input: &str) -> Result<Box<dyn Any>> {
+        if numbered_lines(input).next().is_none() {
+            return Err(Error::Validation("input is empty".to_string()));
+        }
+        Ok(Box::new(self.parse(input)?))
+    }
+
+    fn solve_part(&self, parsed: &dyn Any, part: Part) -> Result<Answer> {
+        let parsed = parsed
+            .downcast_ref::<S::Parsed>()
+            .expect("parsed input should come from the same solver");
+
+        match part {
+            Part::One => Ok(self.part1(parsed)?.into()),
+            Part::Two => Ok(self.part2(parsed)?.into()),
+        }
+    }
+
+    fn explain_parsed(&self, parsed: &dyn Any, part: Part) -> Result<Explanation> {
+        let parsed = parsed
+            .downcast_ref::<S::Parsed>()
+            .expect("parsed input should come from the same solver");
+
+        self.explain(parsed, part)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct LineCount;
+
+    impl Solver for LineCount {
+        type Parsed = Vec<String>;
+        type Part1 = usize;
+        type Part2 = String;
+
+        fn parse(&self, input: &str) -> Result<Vec<String>> {
+            if input.is_empty() {
+                return Err(Error::Validation("empty input".to_string()));
+            }
+            Ok(input.lines().map(|line| line.to_string()).collect())
+        }
+
+        fn part1(&self, parsed: &Vec<String>) -> Result<usize> {
+            Ok(parsed.len())
+        }
+
+        fn part2(&self, parsed: &Vec<String>) -> Result<String> {
+            Ok(parsed.join(","))
+        }
+    }
+
+    #[test]
+    fn parse_part() {
+        assert_eq!("1".parse::<Part>(), Ok(Part::One));
+        assert_eq!("2".parse::<Part>(), Ok(Part::Two));
+        assert!("3".parse::<Part>().is_err());
+    }
+
+    #[test]
+    fn part_serde() {
+        assert_eq!(serde_json::to_string(&Part::Two).unwrap(), "2");
+        assert_eq!(serde_json::from_str::<Part>("1").unwrap(), Part::One);
+        assert!(serde_json::from_str::<Part>("3").is_err());
+    }
+
+    #[test]
+    fn dyn_solver_dispatch() {
+        let solver: Box<dyn DynSolver> = Box::new(LineCount);
+        assert_eq!(solver.solve("a\nb\nc", Part::One).unwrap(), Answer::Number(3));
+        assert_eq!(
+            solver.solve("a\nb\nc", Part::Two).unwrap(),
+            Answer::Text("a,b,c".to_string())
+        );
+    }
+
+    #[test]
+    fn default_explanation_is_empty() {
+        let solver: Box<dyn DynSolver> = Box::new(LineCount);
+        assert_eq!(solver.explain("a\nb", Part::One).unwrap(), Explanation::default());
+    }
+
+    #[test]
+    fn explanation_table() {
+        let mut explanation = Explanation::new(&["line", "value"]);
+        explanation.push_row(vec!["1".to_string(), "12".to_string()]);
+        explanation.push_row(vec!["10".to_string(), "7".to_string()]);
+
+        assert_eq!(explanation.to_string(), "line  value\n1     12\n10    7\n");
+    }
+
+    #[test]
+    fn dyn_solver_parse_error() {
+        let solver: Box<dyn DynSolver> = Box::new(LineCount);
+        assert!(matches!(
+            solver.solve("", Part::One),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn blank_input_is_rejected() {
+        let solver: Box<dyn DynSolver> = Box::new(LineCount);
+        assert!(matches!(solver.check("\n  \n"), Err(Error::Validation(_))));
+        assert!(solver.check("a").is_ok());
+    }
+}
@@ -1,7 +1,23 @@
+//! Day 2: Cube Conundrum.
+//!
+//! Each game reveals handfuls of colored cubes from a bag over several turns.
+//! Part 1 sums the ids of games possible with a given bag, part 2 sums the
+//! power of the smallest bag that makes each game possible.
+
+use log::debug;
+use log::warn;
+use pest::error::LineColLocation;
 use pest::iterators::Pair;
 use pest::Parser;
 use pest_derive::Parser;
 
+use crate::error::try_map_lines;
+use crate::error::Error;
+use crate::error::Result;
+use crate::solver::Explanation;
+use crate::solver::Part;
+use crate::solver::Solver;
+
 #[derive(Parser)]
 #[grammar_inline = r#"
 number = _{ ('0' .. '9')+ }
@@ -10,10 +26,26 @@ num_pulled = { number }
 pull = {  WHITE_SPACE? ~ num_pulled ~ WHITE_SPACE ~ color ~ ","? }
 turn = { pull+ ~ ";"? }
 id = { number }
-game = { "Game " ~ id ~ ":" ~ turn+ ~ NEWLINE? }
+game = { SOI ~ "Game " ~ id ~ ":" ~ turn+ ~ NEWLINE? ~ EOI }
 "#]
 struct StoneGameParser;
 
+fn parse_number(pair: &Pair<'_, Rule>) -> Result<i32> {
+    pair.as_str().parse().map_err(|err| {
+        let (line, column) = pair.as_span().start_pos().line_col();
+        Error::parse(line, column, format!("invalid number {:?}: {}", pair.as_str(), err))
+    })
+}
+
+fn parse_error(err: pest::error::Error<Rule>) -> Error {
+    let (line, column) = match err.line_col {
+        LineColLocation::Pos(pos) => pos,
+        LineColLocation::Span(start, _) => start,
+    };
+    Error::parse(line, column, err.variant.message())
+}
+
+/// A handful of cubes of one color, e.g. `3 blue`.
 #[derive(Debug, PartialEq)]
 pub struct StoneGamePull {
     color: String,
@@ -21,7 +53,15 @@ pub struct StoneGamePull {
 }
 
 impl StoneGamePull {
-    fn parse_rule(rule: Pair<'_, Rule>) -> StoneGamePull {
+    pub fn color(&self) -> &str {
+        &self.color
+    }
+
+    pub fn num_pulled(&self) -> i32 {
+        self.num_pulled
+    }
+
+    fn parse_rule(rule: Pair<'_, Rule>) -> Result<StoneGamePull> {
         let mut color = String::new();
         let mut num_pulled = 0;
         for pull_inner in rule.into_inner() {
@@ -30,32 +70,46 @@ impl StoneGamePull {
                     color = pull_inner.as_str().to_string();
                 }
                 Rule::num_pulled => {
-                    num_pulled = pull_inner.as_str().parse().unwrap();
+                    num_pulled = parse_number(&pull_inner)?;
                 }
                 _ => {}
             }
         }
 
-        StoneGamePull { color, num_pulled }
+        Ok(StoneGamePull { color, num_pulled })
     }
 }
 
+/// One reveal of cubes within a game.
 #[derive(Debug, PartialEq)]
 pub struct StoneGameTurn {
     pulls: Vec<StoneGamePull>,
 }
 
 impl StoneGameTurn {
-    fn parse_rule(rule: Pair<'_, Rule>) -> StoneGameTurn {
+    pub fn pulls(&self) -> &[StoneGamePull] {
+        &self.pulls
+    }
+
+    fn parse_rule(rule: Pair<'_, Rule>) -> Result<StoneGameTurn> {
         let mut pulls = Vec::new();
 
         for rule in rule.into_inner() {
             if rule.as_rule() == Rule::pull {
-                pulls.push(StoneGamePull::parse_rule(rule));
+                pulls.push(StoneGamePull::parse_rule(rule)?);
             }
         }
 
-        StoneGameTurn { pulls }
+        Ok(StoneGameTurn { pulls })
+    }
+
+    // A color named twice in one turn; only its first count is used.
+    fn repeated_color(&self) -> Option<&str> {
+        self.pulls
+            .iter()
+            .enumerate()
+            .find(|(i, pull)| self.pulls[..*i].iter().any(|earlier| earlier.color == pull.color))
+            .map(|(_, pull)| pull.color.as_str())
     }
 
     fn pulls_for_color(&self, color: &str) -> Option<i32> {
@@ -69,6 +123,7 @@ impl StoneGameTurn {
     }
 }
 
+/// A single game line, e.g. `Game 1: 3 blue, 4 red; 2 green`.
 #[derive(Debug, PartialEq)]
 pub struct StoneGame {
     pub id: i32,
@@ -76,39 +131,55 @@ pub struct StoneGame {
 }
 
 impl StoneGame {
-    pub fn parse_lines(input: &str) -> Vec<StoneGame> {
-        let mut games = Vec::new();
-        for line in input.trim().lines() {
-            let line = line.trim();
-            if let Some(game) = StoneGame::parse(line) {
-                games.push(game);
-            } else {
-                println!("failed to parse line: |{}|", line);
+    /// Parses every non-blank line of `input` as a game.
+    pub fn parse_lines(input: &str) -> Result<Vec<StoneGame>> {
+        let games = try_map_lines(input, |line_number, indent, line| {
+            let game = StoneGame::parse(line).map_err(|err| err.at_line(line_number, indent))?;
+            for color in game.turns.iter().filter_map(StoneGameTurn::repeated_color) {
+                warn!(
+                    "line {}: game {} names {} twice in one turn; only the first count is used",
+                    line_number, game.id, color
+                );
             }
-        }
-        games
+            Ok(game)
+        })?;
+
+        debug!("parsed {} games", games.len());
+        Ok(games)
     }
 
-    pub fn parse(input: &str) -> Option<StoneGame> {
-        let parsed = StoneGameParser::parse(Rule::game, input).ok()?;
+    /// The game with the given id.
+    pub fn find(games: &[StoneGame], id: i32) -> Option<&StoneGame> {
+        games.iter().find(|game| game.id == id)
+    }
+
+    /// Parses a single game line.
+    pub fn parse(input: &str) -> Result<StoneGame> {
+        let parsed = StoneGameParser::parse(Rule::game, input).map_err(parse_error)?;
         let mut turns = Vec::new();
         let mut id = 0;
         for game in parsed {
             for inner in game.into_inner() {
                 match inner.as_rule() {
                     Rule::id => {
-                        id = inner.as_str().parse().unwrap();
+                        id = parse_number(&inner)?;
                     }
                     Rule::turn => {
-                        turns.push(StoneGameTurn::parse_rule(inner));
+                        turns.push(StoneGameTurn::parse_rule(inner)?);
                     }
                     _ => {}
                 }
             }
         }
-        Some(StoneGame { id, turns })
+        Ok(StoneGame { id, turns })
+    }
+
+    pub fn turns(&self) -> &[StoneGameTurn] {
+        &self.turns
     }
 
+    /// The most cubes of `color` revealed in any one turn, or `None` if the
+    /// color never came up.
     pub fn max_pulls_for_color(&self, color: &str) -> Option<i32> {
         let mut pull_found = false;
         let mut pulls = 0;
@@ -128,6 +199,8 @@ impl StoneGame {
         }
     }
 
+    /// Whether the game could have been played with a bag holding the given
+    /// number of cubes.
     pub fn possible_for(&self, red: i32, blue: i32, green: i32) -> bool {
         let game_reds = self.max_pulls_for_color("red").unwrap_or(0);
         let game_blues = self.max_pulls_for_color("blue").unwrap_or(i32::MAX);
@@ -136,6 +209,8 @@ impl StoneGame {
         red >= game_reds && blue >= game_blues && green >= game_greens
     }
 
+    /// The product of the fewest cubes of each color that make the game
+    /// possible.
     pub fn power(&self) -> i32 {
         let game_reds = self.max_pulls_for_color("red").unwrap_or(1);
         let game_blues = self.max_pulls_for_color("blue").unwrap_or(1);
@@ -144,6 +219,107 @@ impl StoneGame {
     }
 }
 
+/// The most cubes of each color a game revealed, and whether the bag could
+/// have held them.
+#[derive(Debug, PartialEq)]
+pub struct GameSummary {
+    pub id: i32,
+    pub red: Option<i32>,
+    pub green: Option<i32>,
+    pub blue: Option<i32>,
+    pub possible: bool,
+}
+
+/// Day 2 solver. The bag contents are only used by part 1 and default to the
+/// puzzle's 12 red, 13 green and 14 blue cubes.
+pub struct Day2 {
+    pub red: i32,
+    pub green: i32,
+    pub blue: i32,
+}
+
+impl Default for Day2 {
+    fn default() -> Self {
+        Day2 {
+            red: 12,
+            green: 13,
+            blue: 14,
+        }
+    }
+}
+
+impl Day2 {
+    /// Summarizes each game against this solver's bag.
+    pub fn summarize(&self, games: &[StoneGame]) -> Vec<GameSummary> {
+        games
+            .iter()
+            .map(|game| GameSummary {
+                id: game.id,
+                red: game.max_pulls_for_color("red"),
+                green: game.max_pulls_for_color("green"),
+                blue: game.max_pulls_for_color("blue"),
+                possible: game.possible_for(self.red, self.blue, self.green),
+            })
+            .collect()
+    }
+}
+
+impl Solver for Day2 {
+    type Parsed = Vec<StoneGame>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, input: &str) -> Result<Vec<StoneGame>> {
+        StoneGame::parse_lines(input)
+    }
+
+    fn part1(&self, games: &Vec<StoneGame>) -> Result<i32> {
+        let mut id_sum = 0;
+
+        for game in games {
+            if game.possible_for(self.red, self.blue, self.green) {
+                id_sum += game.id;
+            }
+        }
+
+        Ok(id_sum)
+    }
+
+    fn part2(&self, games: &Vec<StoneGame>) -> Result<i32> {
+        let mut power_sum = 0;
+
+        for game in games {
+            power_sum += game.power();
+        }
+
+        Ok(power_sum)
+    }
+
+    fn explain(&self, games: &Vec<StoneGame>, part: Part) -> Result<Explanation> {
+        let count = |pulls: Option<i32>| pulls.map(|n| n.to_string()).unwrap_or("-".to_string());
+
+        let last_column = match part {
+            Part::One => "result",
+            Part::Two => "power",
+        };
+        let mut explanation = Explanation::new(&["game", "red", "green", "blue", last_column]);
+        for (game, summary) in games.iter().zip(self.summarize(games)) {
+            let last = match part {
+                Part::One => if summary.possible { "pass" } else { "fail" }.to_string(),
+                Part::Two => game.power().to_string(),
+            };
+            explanation.push_row(vec![
+                summary.id.to_string(),
+                count(summary.red),
+                count(summary.green),
+                count(summary.blue),
+                last,
+            ]);
+        }
+        Ok(explanation)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -185,10 +361,69 @@ mod test {
         assert!(stonegame.max_pulls_for_color("green") == Some(13));
     }
 
+    #[test]
+    fn day2_solver_example() {
+        let input = "
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+        ";
+        let solver = Day2::default();
+        let games = solver.parse(input).unwrap();
+
+        assert_eq!(solver.part1(&games).unwrap(), 8);
+        assert_eq!(solver.part2(&games).unwrap(), 2286);
+    }
+
+    #[test]
+    fn find_game() {
+        let input = "
+        Game 1: 3 blue, 4 red
+        Game 7: 1 blue, 2 green
+        ";
+        let games = StoneGame::parse_lines(input).unwrap();
+
+        let game = StoneGame::find(&games, 7).unwrap();
+        assert_eq!(game.max_pulls_for_color("green"), Some(2));
+        assert!(StoneGame::find(&games, 2).is_none());
+    }
+
+    #[test]
+    fn summarize_games() {
+        let input = "
+        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        ";
+        let games = StoneGame::parse_lines(input).unwrap();
+
+        assert_eq!(
+            Day2::default().summarize(&games),
+            vec![
+                GameSummary { id: 1, red: Some(4), green: Some(2), blue: Some(6), possible: true },
+                GameSummary { id: 3, red: Some(20), green: Some(13), blue: Some(6), possible: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_error_position() {
+        let input = "
+        Game 1: 3 blue, 4 red
+        Game 2: 1 blue, 2 gren
+        ";
+
+        match StoneGame::parse_lines(input) {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (3, 27)),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
     #[test]
     fn reads_input() {
         let input = std::fs::read_to_string("inputs/day2.txt").unwrap();
-        let games = StoneGame::parse_lines(&input);
+        let games = StoneGame::parse_lines(&input).unwrap();
         assert_eq!(games.len(), 100);
     }
 
@@ -198,8 +433,8 @@ mod test {
         let parsed = StoneGame::parse(input);
 
         assert_eq!(
-            parsed,
-            Some(StoneGame {
+            parsed.unwrap(),
+            StoneGame {
                 id: 1,
                 turns: vec![
                     StoneGameTurn {
@@ -237,7 +472,7 @@ mod test {
                         }]
                     }
                 ]
-            })
+            }
         );
     }
 
@@ -250,7 +485,7 @@ mod test {
         let parsed = StoneGame::parse_lines(input);
 
         assert_eq!(
-            parsed,
+            parsed.unwrap(),
             vec![
                 StoneGame {
                     id: 1,
@@ -1,3 +1,7 @@
+//! An alternative Day 2 parser for the cube game built on `nom` rather than
+//! `pest`. It produces typed colors instead of strings but is not wired into
+//! the solver.
+
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_while1;
@@ -7,15 +11,21 @@ use nom::multi::separated_list1;
 use nom::sequence::tuple;
 use nom::IResult;
 
+use crate::error::try_map_lines;
+use crate::error::Error;
+use crate::error::Result;
+
+/// A cube color.
 #[derive(Debug, PartialEq)]
-enum Color {
+pub enum Color {
     Red,
     Green,
     Blue,
 }
 
 impl Color {
-    fn parse(input: &str) -> IResult<&str, Color> {
+    /// Parses `red`, `green` or `blue`.
+    pub fn parse(input: &str) -> IResult<&str, Color> {
         map_res(alt((tag("red"), tag("green"), tag("blue"))), |s| match s {
             "red" => Ok(Color::Red),
             "green" => Ok(Color::Green),
@@ -25,15 +35,17 @@ impl Color {
     }
 }
 
+/// A handful of cubes of a single color, e.g. `3 blue`.
 #[derive(Debug, PartialEq)]
-struct Pull {
-    color: Color,
-    count: i32,
+pub struct Pull {
+    pub color: Color,
+    pub count: i32,
 }
 
 impl Pull {
-    fn parse(input: &str) -> IResult<&str, Pull> {
-        let (input, count) = map_res(take_while1(|c: char| c.is_digit(10)), |count_str: &str| {
+    /// Parses a count followed by a color.
+    pub fn parse(input: &str) -> IResult<&str, Pull> {
+        let (input, count) = map_res(take_while1(|c: char| c.is_ascii_digit()), |count_str: &str| {
             count_str.parse::<i32>()
         })(input)?;
         let (input, _) = multispace0(input)?;
@@ -43,28 +55,32 @@ impl Pull {
     }
 }
 
+/// One reveal of cubes, a comma-separated list of pulls.
 #[derive(Debug, PartialEq)]
-struct Turn {
-    pulls: Vec<Pull>,
+pub struct Turn {
+    pub pulls: Vec<Pull>,
 }
 
 impl Turn {
-    fn parse(input: &str) -> IResult<&str, Turn> {
+    /// Parses pulls separated by commas.
+    pub fn parse(input: &str) -> IResult<&str, Turn> {
         let (input, pulls) = separated_list1(tuple((tag(","), multispace0)), Pull::parse)(input)?;
         Ok((input, Turn { pulls }))
     }
 }
 
+/// A full game line, e.g. `Game 1: 3 blue, 4 red; 2 green`.
 #[derive(Debug, PartialEq)]
-struct Game {
-    id: i32,
-    turns: Vec<Turn>,
+pub struct Game {
+    pub id: i32,
+    pub turns: Vec<Turn>,
 }
 
 impl Game {
-    fn parse_line(input: &str) -> IResult<&str, Game> {
+    /// Parses a single game line.
+    pub fn parse_line(input: &str) -> IResult<&str, Game> {
         let (input, _) = tuple((multispace0, tag("Game"), multispace0))(input)?;
-        let (input, id) = map_res(take_while1(|c: char| c.is_digit(10)), |count_str: &str| {
+        let (input, id) = map_res(take_while1(|c: char| c.is_ascii_digit()), |count_str: &str| {
             count_str.parse::<i32>()
         })(input)?;
         let (input, _) = tuple((tag(":"), multispace0))(input)?;
@@ -72,14 +88,23 @@ impl Game {
         Ok((input, Game { id, turns }))
     }
 
-    fn parse(input: &str) -> Vec<Game> {
-        let mut games = Vec::new();
-        for line in input.trim().lines() {
-            if let Ok((_, game)) = Game::parse_line(line) {
-                games.push(game);
+    /// Parses every non-blank line of `input` as a game, failing at the
+    /// first line that doesn't parse.
+    pub fn parse(input: &str) -> Result<Vec<Game>> {
+        try_map_lines(input, |line_number, indent, line| match Game::parse_line(line) {
+            Ok(("", game)) => Ok(game),
+            Ok((remaining, _)) => {
+                Err(Error::parse_at(line, line.len() - remaining.len(), "unexpected trailing input")
+                    .at_line(line_number, indent))
             }
-        }
-        games
+            Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+                Err(Error::parse_at(line, line.len() - err.input.len(), "malformed game")
+                    .at_line(line_number, indent))
+            }
+            Err(nom::Err::Incomplete(_)) => {
+                Err(Error::parse(line_number, indent + 1, "incomplete game"))
+            }
+        })
     }
 }
 
@@ -177,7 +202,8 @@ mod test {
             Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
             Game 10: 10 red, 5 blue; 5 red; 6 blue
             "
-            ),
+            )
+            .unwrap(),
             vec![
                 Game {
                     id: 2,
@@ -256,4 +282,13 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn parse_game_lines_rejects_bad_lines() {
+        match Game::parse("Game 1: 3 blue\nGame 2: 4 purple\n") {
+            Err(Error::Parse { line, column, .. }) => assert_eq!((line, column), (2, 11)),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(Game::parse("Game 1: 3 blue; x"), Err(Error::Parse { line: 1, .. })));
+    }
 }
@@ -0,0 +1,279 @@
+//! A local cache of puzzle inputs that can fill itself from a pluggable
+//! source.
+//!
+//! Inputs are cached as `<root>/day<N>.txt`, the same layout `run-all` and
+//! `verify` read. The root defaults to `inputs` and can be moved with the
+//! `AOC_INPUT_ROOT` environment variable. When `AOC_INPUT_SOURCE` is set,
+//! missing inputs are fetched from it: an `http://` URL selects
+//! [`HttpSource`], anything else is treated as a [`DirectorySource`].
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use log::info;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::runner;
+
+/// The puzzle year this crate solves.
+pub const YEAR: u32 = 2023;
+
+pub const ROOT_VAR: &str = "AOC_INPUT_ROOT";
+pub const SOURCE_VAR: &str = "AOC_INPUT_SOURCE";
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// Somewhere a missing input can be fetched from.
+pub trait InputSource {
+    fn fetch(&self, year: u32, day: u32) -> Result<String>;
+}
+
+/// Copies inputs out of a shared directory laid out as
+/// `<root>/<year>/day<N>.txt`.
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(root: impl Into<PathBuf>) -> DirectorySource {
+        DirectorySource { root: root.into() }
+    }
+}
+
+impl InputSource for DirectorySource {
+    fn fetch(&self, year: u32, day: u32) -> Result<String> {
+        let path = runner::input_path(&self.root.join(year.to_string()), day);
+        std::fs::read_to_string(&path).map_err(|err| Error::io(path, err))
+    }
+}
+
+/// Downloads inputs from `<base_url>/<year>/day/<N>/input`, sending the
+/// session cookie when one is configured.
+pub struct HttpSource {
+    base_url: String,
+    session: Option<String>,
+}
+
+impl HttpSource {
+    pub fn new(base_url: impl Into<String>, session: Option<String>) -> HttpSource {
+        HttpSource {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            session,
+        }
+    }
+}
+
+impl InputSource for HttpSource {
+    fn fetch(&self, year: u32, day: u32) -> Result<String> {
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+        let mut request = ureq::get(&url);
+        if let Some(session) = &self.session {
+            request = request.set("Cookie", &format!("session={}", session));
+        }
+
+        let response = request
+            .call()
+            .map_err(|err| Error::network(&url, err))?;
+        response.into_string().map_err(|err| Error::network(&url, err))
+    }
+}
+
+/// Resolves days to cached input files, fetching them from the source on a
+/// cache miss.
+pub struct InputStore {
+    root: PathBuf,
+    year: u32,
+    source: Option<Box<dyn InputSource>>,
+}
+
+impl InputStore {
+    pub fn new(root: impl Into<PathBuf>, year: u32) -> InputStore {
+        InputStore {
+            root: root.into(),
+            year,
+            source: None,
+        }
+    }
+
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> InputStore {
+        self.root = root.into();
+        self
+    }
+
+    pub fn with_source(mut self, source: Box<dyn InputSource>) -> InputStore {
+        self.source = Some(source);
+        self
+    }
+
+    /// A store configured from `AOC_INPUT_ROOT`, `AOC_INPUT_SOURCE` and
+    /// `AOC_SESSION`.
+    pub fn from_env() -> InputStore {
+        let root = std::env::var(ROOT_VAR).unwrap_or_else(|_| "inputs".to_string());
+        let store = InputStore::new(root, YEAR);
+
+        match std::env::var(SOURCE_VAR) {
+            Ok(source) if source.starts_with("http://") || source.starts_with("https://") => {
+                let session = std::env::var(SESSION_VAR).ok();
+                store.with_source(Box::new(HttpSource::new(source, session)))
+            }
+            Ok(source) => store.with_source(Box::new(DirectorySource::new(source))),
+            Err(_) => store,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the input for `day` is cached.
+    pub fn path(&self, day: u32) -> PathBuf {
+        runner::input_path(&self.root, day)
+    }
+
+    /// The input for `day`, fetched into the cache first if it's missing.
+    pub fn get(&self, day: u32) -> Result<String> {
+        let path = self.path(day);
+        if path.exists() {
+            info!("reading day {} from {}", day, path.display());
+            return std::fs::read_to_string(&path).map_err(|err| Error::io(path, err));
+        }
+
+        let Some(source) = &self.source else {
+            return Err(Error::io(
+                path,
+                std::io::Error::new(std::io::ErrorKind::NotFound, "input not cached and no source configured"),
+            ));
+        };
+
+        info!("fetching day {} into {}", day, path.display());
+        let input = source.fetch(self.year, day)?;
+        std::fs::create_dir_all(&self.root).map_err(|err| Error::io(&self.root, err))?;
+        std::fs::write(&path, &input).map_err(|err| Error::io(&path, err))?;
+
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc_2023_store_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // Answers a single request with `body` and reports the request line.
+    fn serve_once(body: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push(line.trim().to_string());
+            }
+            sender.send(request.join("\n")).unwrap();
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        (url, receiver)
+    }
+
+    struct FixedSource;
+
+    impl InputSource for FixedSource {
+        fn fetch(&self, year: u32, day: u32) -> Result<String> {
+            Ok(format!("{} {}", year, day))
+        }
+    }
+
+    #[test]
+    fn reads_cached_input() {
+        let root = temp_dir("cached");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("day3.txt"), "cached").unwrap();
+
+        let store = InputStore::new(&root, YEAR).with_source(Box::new(FixedSource));
+        assert_eq!(store.get(3).unwrap(), "cached");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn fetches_and_caches_missing_input() {
+        let root = temp_dir("fetch");
+        let store = InputStore::new(&root, YEAR).with_source(Box::new(FixedSource));
+
+        assert_eq!(store.get(4).unwrap(), "2023 4");
+        assert_eq!(std::fs::read_to_string(root.join("day4.txt")).unwrap(), "2023 4");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn missing_input_without_source() {
+        let store = InputStore::new(temp_dir("missing"), YEAR);
+        assert!(matches!(store.get(1), Err(Error::Io { .. })));
+    }
+
+    #[test]
+    fn directory_source() {
+        let shared = temp_dir("shared");
+        std::fs::create_dir_all(shared.join("2023")).unwrap();
+        std::fs::write(shared.join("2023").join("day2.txt"), "Game 1: 1 red").unwrap();
+
+        let source = DirectorySource::new(&shared);
+        assert_eq!(source.fetch(2023, 2).unwrap(), "Game 1: 1 red");
+        assert!(source.fetch(2022, 2).is_err());
+
+        std::fs::remove_dir_all(shared).unwrap();
+    }
+
+    #[test]
+    fn http_source() {
+        let (url, requests) = serve_once("1abc2\n");
+        let source = HttpSource::new(url, Some("secret".to_string()));
+
+        assert_eq!(source.fetch(2023, 1).unwrap(), "1abc2\n");
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("GET /2023/day/1/input HTTP/1.1"));
+        assert!(request.contains("Cookie: session=secret"));
+    }
+
+    #[test]
+    fn https_source() {
+        // The stand-in hangs up without a handshake, so this fails, but only
+        // after a TLS connection was attempted.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || drop(listener.accept()));
+
+        match HttpSource::new(url, None).fetch(2023, 1) {
+            Err(Error::Network { message, .. }) => assert!(!message.contains("TLS"), "{}", message),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+//! Posting answers to the puzzle site and recording what it said.
+//!
+//! The site is reached through a [`SubmitClient`], so tests (or a local
+//! stand-in server) can take its place. Every verdict is recorded in a
+//! [`History`], and answers the history rules out are never sent (see
+//! [`guard`]).
+
+use std::path::Path;
+
+use crate::answers::Answers;
+use crate::error::Error;
+use crate::error::Result;
+use crate::guard;
+use crate::history::History;
+use crate::history::Submission;
+use crate::history::Verdict;
+use crate::solver::Answer;
+use crate::solver::Part;
+use crate::store::SESSION_VAR;
+
+/// The site answers are posted to, unless `AOC_URL` says otherwise.
+pub const DEFAULT_URL: &str = "https://adventofcode.com";
+pub const URL_VAR: &str = "AOC_URL";
+
+/// Somewhere answers can be submitted.
+pub trait SubmitClient {
+    fn submit(&self, year: u32, day: u32, part: Part, answer: &Answer) -> Result<Verdict>;
+}
+
+/// Reads the verdict out of the page the site returns for a submission.
+pub fn parse_verdict(page: &str) -> Option<Verdict> {
+    if page.contains("That's the right answer") {
+        Some(Verdict::Correct)
+    } else if page.contains("too recently") {
+        Some(Verdict::Wait)
+    } else if page.contains("your answer is too high") {
+        Some(Verdict::TooHigh)
+    } else if page.contains("your answer is too low") {
+        Some(Verdict::TooLow)
+    } else if page.contains("That's not the right answer") {
+        Some(Verdict::Incorrect)
+    } else {
+        None
+    }
+}
+
+/// Posts answers as `level=<part>&answer=<answer>` to
+/// `<base_url>/<year>/day/<N>/answer`, sending the session cookie when one
+/// is configured.
+pub struct HttpClient {
+    base_url: String,
+    session: Option<String>,
+}
+
+impl HttpClient {
+    pub fn new(base_url: impl Into<String>, session: Option<String>) -> HttpClient {
+        HttpClient {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            session,
+        }
+    }
+
+    /// A client configured from `AOC_URL` and `AOC_SESSION`.
+    pub fn from_env() -> HttpClient {
+        let base_url = std::env::var(URL_VAR).unwrap_or_else(|_| DEFAULT_URL.to_string());
+        HttpClient::new(base_url, std::env::var(SESSION_VAR).ok())
+    }
+}
+
+impl SubmitClient for HttpClient {
+    fn submit(&self, year: u32, day: u32, part: Part, answer: &Answer) -> Result<Verdict> {
+        let url = format!("{}/{}/day/{}/answer", self.base_url, year, day);
+        let mut request = ureq::post(&url);
+        if let Some(session) = &self.session {
+            request = request.set("Cookie", &format!("session={}", session));
+        }
+
+        let level = part.to_string();
+        let answer = answer.to_string();
+        let page = request
+            .send_form(&[("level", &level), ("answer", &answer)])
+            .map_err(|err| Error::network(&url, err))?
+            .into_string()
+            .map_err(|err| Error::network(&url, err))?;
+
+        parse_verdict(&page).ok_or_else(|| Error::network(&url, "unrecognized response"))
+    }
+}
+
+/// Submits `answer` unless [`guard::check`] refuses it, and records the
+/// verdict. An answer already confirmed correct isn't sent again.
+pub fn submit(
+    client: &dyn SubmitClient,
+    history: &mut History,
+    year: u32,
+    day: u32,
+    part: Part,
+    answer: &Answer,
+) -> Result<Verdict> {
+    if history.correct(day, part) == Some(answer) {
+        return Ok(Verdict::Correct);
+    }
+
+    guard::check(history, day, part, answer).map_err(|refusal| {
+        Error::Refused(format!(
+            "not submitting {} for day {} part {}: {}",
+            answer, day, part, refusal
+        ))
+    })?;
+
+    let verdict = client.submit(year, day, part, answer)?;
+    history.record(Submission {
+        day,
+        part,
+        answer: answer.clone(),
+        verdict,
+    });
+
+    Ok(verdict)
+}
+
+/// Records an answer the site confirmed in the answers file at
+/// `answers_path`, creating the file if needed, so `verify` catches later
+/// regressions.
+pub fn record_correct(answers_path: &Path, day: u32, part: Part, answer: &Answer) -> Result<()> {
+    let mut answers = Answers::load_or_default(answers_path)?;
+    if answers.expected(day, part) == Some(answer) {
+        return Ok(());
+    }
+
+    answers.record(day, part, answer.clone());
+    answers.save(answers_path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Read;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    // Answers with a scripted verdict and remembers what was posted.
+    struct FakeClient {
+        verdict: Verdict,
+        posted: RefCell<Vec<Answer>>,
+    }
+
+    impl FakeClient {
+        fn new(verdict: Verdict) -> FakeClient {
+            FakeClient {
+                verdict,
+                posted: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl SubmitClient for FakeClient {
+        fn submit(&self, _year: u32, _day: u32, _part: Part, answer: &Answer) -> Result<Verdict> {
+            self.posted.borrow_mut().push(answer.clone());
+            Ok(self.verdict)
+        }
+    }
+
+    // Answers a single request with `page` and reports the request line and
+    // body.
+    fn serve_once(page: &'static str) -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            sender
+                .send((request_line.trim().to_string(), String::from_utf8(body).unwrap()))
+                .unwrap();
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                page.len(),
+                page
+            )
+            .unwrap();
+        });
+
+        (url, receiver)
+    }
+
+    #[test]
+    fn verdicts_from_pages() {
+        let page = |text: &str| format!("<main><article><p>{}</p></article></main>", text);
+
+        assert_eq!(parse_verdict(&page("That's the right answer!")), Some(Verdict::Correct));
+        assert_eq!(
+            parse_verdict(&page("That's not the right answer; your answer is too high.")),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(
+            parse_verdict(&page("That's not the right answer; your answer is too low.")),
+            Some(Verdict::TooLow)
+        );
+        assert_eq!(
+            parse_verdict(&page("That's not the right answer.")),
+            Some(Verdict::Incorrect)
+        );
+        assert_eq!(
+            parse_verdict(&page("You gave an answer too recently.")),
+            Some(Verdict::Wait)
+        );
+        assert_eq!(parse_verdict("<html></html>"), None);
+    }
+
+    #[test]
+    fn http_client() {
+        let page = "<p>That's not the right answer; your answer is too low.</p>";
+        let (url, requests) = serve_once(page);
+        let client = HttpClient::new(url, Some("secret".to_string()));
+
+        let verdict = client.submit(2023, 4, Part::Two, &Answer::Number(29)).unwrap();
+        assert_eq!(verdict, Verdict::TooLow);
+
+        let (request_line, body) = requests.recv().unwrap();
+        assert_eq!(request_line, "POST /2023/day/4/answer HTTP/1.1");
+        assert_eq!(body, "level=2&answer=29");
+    }
+
+    #[test]
+    fn http_failures_are_network_errors() {
+        let client = HttpClient::new("http://127.0.0.1:1", None);
+        let result = client.submit(2023, 4, Part::Two, &Answer::Number(29));
+        assert!(matches!(result, Err(Error::Network { .. })));
+
+        let (url, _requests) = serve_once("<html></html>");
+        let result = HttpClient::new(url, None).submit(2023, 4, Part::Two, &Answer::Number(29));
+        assert!(matches!(result, Err(Error::Network { .. })));
+    }
+
+    #[test]
+    fn default_client_speaks_https() {
+        assert!(DEFAULT_URL.starts_with("https://"));
+
+        // The stand-in hangs up without a handshake, so this fails, but only
+        // after a TLS connection was attempted.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("https://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || drop(listener.accept()));
+
+        let client = HttpClient::new(url, None);
+        match client.submit(2023, 4, Part::Two, &Answer::Number(29)) {
+            Err(Error::Network { message, .. }) => assert!(!message.contains("TLS"), "{}", message),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn records_verdicts() {
+        let client = FakeClient::new(Verdict::TooHigh);
+        let mut history = History::default();
+
+        let answer = Answer::Number(31);
+        let verdict = submit(&client, &mut history, 2023, 4, Part::Two, &answer).unwrap();
+        assert_eq!(verdict, Verdict::TooHigh);
+        assert_eq!(history.verdict_for(4, Part::Two, &answer), Some(Verdict::TooHigh));
+    }
+
+    #[test]
+    fn never_resubmits_wrong_answers() {
+        let client = FakeClient::new(Verdict::Incorrect);
+        let mut history = History::default();
+
+        submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(31)).unwrap();
+        let again = submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(31));
+
+        assert!(matches!(again, Err(Error::Refused(_))));
+        assert_eq!(client.posted.borrow().len(), 1);
+    }
+
+    #[test]
+    fn refuses_answers_out_of_bounds() {
+        let client = FakeClient::new(Verdict::TooLow);
+        let mut history = History::default();
+
+        submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(20)).unwrap();
+        let lower = submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(19));
+
+        assert!(matches!(lower, Err(Error::Refused(_))));
+        assert_eq!(client.posted.borrow().len(), 1);
+    }
+
+    #[test]
+    fn resubmits_after_wait() {
+        let client = FakeClient::new(Verdict::Wait);
+        let mut history = History::default();
+
+        submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(30)).unwrap();
+        submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(30)).unwrap();
+
+        assert_eq!(client.posted.borrow().len(), 2);
+    }
+
+    #[test]
+    fn solved_parts_are_not_resubmitted() {
+        let client = FakeClient::new(Verdict::Correct);
+        let mut history = History::default();
+
+        submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(30)).unwrap();
+        let same = submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(30));
+        let other = submit(&client, &mut history, 2023, 4, Part::Two, &Answer::Number(31));
+
+        assert_eq!(same.unwrap(), Verdict::Correct);
+        assert!(matches!(other, Err(Error::Refused(_))));
+        assert_eq!(client.posted.borrow().len(), 1);
+    }
+    #[test]
+    fn records_correct_answers() {
+        let path = std::env::temp_dir().join("aoc_2023_submit_answers.toml");
+        let _ = std::fs::remove_file(&path);
+
+        record_correct(&path, 4, Part::One, &Answer::Number(13)).unwrap();
+        record_correct(&path, 4, Part::Two, &Answer::Number(30)).unwrap();
+        let answers = Answers::load(&path).unwrap();
+        assert_eq!(answers.expected(4, Part::One), Some(&Answer::Number(13)));
+        assert_eq!(answers.expected(4, Part::Two), Some(&Answer::Number(30)));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
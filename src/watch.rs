@@ -0,0 +1,87 @@
+//! Polling for changes to an input file so a day can be re-run as it's
+//! edited.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::solver::Answer;
+
+/// Detects changes to a file by polling its modification time and size.
+pub struct FileWatcher {
+    path: PathBuf,
+    last_seen: Option<(SystemTime, u64)>,
+}
+
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+impl FileWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> FileWatcher {
+        let path = path.into();
+        let last_seen = fingerprint(&path);
+        FileWatcher { path, last_seen }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the last call (or since the watcher
+    /// was created).
+    pub fn changed(&mut self) -> bool {
+        let current = fingerprint(&self.path);
+        if current == self.last_seen {
+            false
+        } else {
+            self.last_seen = current;
+            true
+        }
+    }
+}
+
+/// Describes how an answer moved between two runs.
+pub fn describe_change(previous: Option<&Answer>, current: &Answer) -> String {
+    match previous {
+        None => format!("{}", current),
+        Some(previous) if previous == current => format!("{} (unchanged)", current),
+        Some(previous) => format!("{} (was {})", current, previous),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_changes() {
+        let path = std::env::temp_dir().join("aoc_2023_watch_test.txt");
+        std::fs::write(&path, "1abc2").unwrap();
+
+        let mut watcher = FileWatcher::new(&path);
+        assert!(!watcher.changed());
+
+        std::fs::write(&path, "1abc2\npqr3stu8vwx").unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+    }
+
+    #[test]
+    fn describes_changes() {
+        assert_eq!(describe_change(None, &Answer::Number(142)), "142");
+        assert_eq!(
+            describe_change(Some(&Answer::Number(142)), &Answer::Number(142)),
+            "142 (unchanged)"
+        );
+        assert_eq!(
+            describe_change(Some(&Answer::Number(142)), &Answer::Number(281)),
+            "281 (was 142)"
+        );
+    }
+}